@@ -0,0 +1,166 @@
+use std::collections::HashSet;
+
+use keyboard_codes::Modifier;
+
+use crate::{
+    config::{Action, Keybind, Layer, LayerActivation, ModifierKeys, Trigger},
+    keycode::Keycode,
+    mapper::map_modifier,
+    shortcut::Shortcut,
+};
+
+// One concrete set of keys a Keybind expands to, e.g. Shift+1 gives one for LShift and one for RShift
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Keybinding {
+    // Each earlier step of a sequence, with every key set that step can be pressed as
+    pub leader: Vec<Vec<HashSet<Keycode>>>,
+    pub keys: HashSet<Keycode>,
+    pub action: Action,
+    pub trigger: Trigger,
+    pub exact_modifiers: Option<bool>,
+    pub layer: Option<String>,
+    pub priority: i32,
+    // How many modifiers were written side specific, e.g. 1 for RightShift+1 and 0 for Shift+1
+    pub specific_modifiers: usize,
+}
+
+// The keys that switch a layer on, expanded the same way as a Keybinding
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayerBinding {
+    pub name: String,
+    pub activation: LayerActivation,
+    pub keys: Vec<HashSet<Keycode>>,
+}
+
+fn cartesian_product(keycodes: Vec<HashSet<Keycode>>) -> Vec<HashSet<Keycode>> {
+    if keycodes.is_empty() {
+        return vec![HashSet::new()];
+    }
+    let set = &keycodes[0];
+    let cartesian = cartesian_product(keycodes[1..].to_vec());
+
+    let mut product = Vec::new();
+
+    for keycode in set {
+        for set in &cartesian {
+            let mut current_set = set.clone();
+            current_set.insert(*keycode);
+            product.push(current_set);
+        }
+    }
+
+    product
+}
+
+pub fn expand_combinations(key: Keycode, modifiers: Vec<Modifier>) -> Vec<HashSet<Keycode>> {
+    if modifiers.is_empty() {
+        let mut set = HashSet::new();
+        set.insert(key);
+        return vec![set];
+    }
+    let mod_keycodes = modifiers.iter()
+        .map(|m| {
+            let mut set = HashSet::new();
+
+            match map_modifier(m) {
+                (base_modifier, None) => {
+                    set.insert(base_modifier);
+                }
+                (base_modifier, Some(add_modifier)) => {
+                    set.insert(base_modifier);
+                    set.insert(add_modifier);
+                }
+            }
+            set
+        })
+        .collect::<Vec<HashSet<Keycode>>>();
+
+    let mut key_keycode = HashSet::new();
+    key_keycode.insert(key);
+
+    let mut joined_keycodes = mod_keycodes.clone();
+    joined_keycodes.push(key_keycode);
+
+    cartesian_product(joined_keycodes)
+}
+
+fn expand_shortcut(shortcut: &Shortcut) -> Vec<HashSet<Keycode>> {
+    let mut expanded = expand_combinations(shortcut.key, shortcut.modifiers.clone());
+    for keys in &mut expanded {
+        keys.extend(shortcut.buttons.iter().copied());
+    }
+    expanded
+}
+
+pub fn expand_keybind(keybind: &Keybind) -> Vec<Keybinding> {
+    let leader = keybind.leader.iter().map(expand_shortcut).collect::<Vec<_>>();
+    let expanded = expand_shortcut(&keybind.shortcut);
+    let specific_modifiers = keybind.shortcut.modifiers.iter()
+        .filter(|m| map_modifier(m).1.is_none())
+        .count();
+    expanded
+        .into_iter()
+        .map(|keys| Keybinding {
+            leader: leader.clone(),
+            keys,
+            action: keybind.action.clone(),
+            trigger: keybind.trigger,
+            exact_modifiers: keybind.exact_modifiers,
+            layer: keybind.layer.clone(),
+            priority: keybind.priority,
+            specific_modifiers,
+        })
+        .collect()
+}
+
+// Which of two keybindings wins when both match, higher first: an explicit priority= beats
+// everything, then more keys beat fewer, then side specific modifiers beat generic ones
+pub fn precedence(keybinding: &Keybinding) -> (i32, usize, usize) {
+    (keybinding.priority, keybinding.keys.len(), keybinding.specific_modifiers)
+}
+
+// Sorted by precedence. The sort is stable, so full ties keep ini order and the earlier bind wins
+pub fn build_keybindings(keybinds_config: &[Keybind]) -> Vec<Keybinding> {
+    let mut keybindings = keybinds_config.iter()
+                .flat_map(expand_keybind)
+                .collect::<Vec<Keybinding>>();
+
+    keybindings.sort_by_key(|k| std::cmp::Reverse(precedence(k)));
+
+    keybindings
+}
+
+pub fn build_layers(layers: &[Layer]) -> Vec<LayerBinding> {
+    layers.iter()
+        .map(|layer| LayerBinding {
+            name: layer.name.clone(),
+            activation: layer.activation,
+            keys: expand_shortcut(&layer.shortcut),
+        })
+        .collect()
+}
+
+pub fn is_all_keybinding_keys_pressed(keybindings: &HashSet<Keycode>, pressed_keys: &[Keycode]) -> bool {
+    for key in keybindings {
+        if !pressed_keys.contains(key) {
+            return false;
+        }
+    }
+    true
+}
+
+// Like is_all_keybinding_keys_pressed, but an exact keybinding also fails when any key in
+// `modifier_keys` is held that the keybinding doesn't include. Other extra keys, like movement, are fine
+pub fn is_keybinding_matched(keybinding: &Keybinding, pressed_keys: &[Keycode], is_exact: bool, modifier_keys: &ModifierKeys) -> bool {
+    if !is_all_keybinding_keys_pressed(&keybinding.keys, pressed_keys) {
+        return false;
+    }
+    !is_exact || !pressed_keys.iter().any(|k| modifier_keys.contains(k) && !keybinding.keys.contains(k))
+}
+
+// Modifiers first, then the rest by name, e.g. "LShift+Key1"
+pub fn format_keys(keys: &HashSet<Keycode>) -> String {
+    let mut keys = keys.iter().collect::<Vec<&Keycode>>();
+    keys.sort_by_key(|k| (!k.is_modifier(), k.as_str()));
+    keys.iter().map(|k| k.as_str()).collect::<Vec<_>>().join("+")
+}
@@ -0,0 +1,844 @@
+use std::{fmt::Display, fs, path::Path, str::FromStr, time::Duration};
+
+use keyboard_codes::parse_modifier_with_aliases;
+
+use crate::{
+    conflicts::find_conflicts,
+    diagnostics::{Diagnostic, DiagnosticKind},
+    ini_document::{IniDocument, IniEntry},
+    keycode::Keycode,
+    mapper::map_modifier,
+    schema::{migrate_document, CONFIG_VERSION},
+    shortcut::Shortcut,
+    spells::{parse_spell, spell_label},
+};
+
+pub const DEFAULT_CYCLE_DEBOUNCE_MILLISECONDS: u64 = 200;
+pub const DEFAULT_SEQUENCE_TIMEOUT_MILLISECONDS: u64 = 1000;
+
+// Used when the config can't be read or has no [keybinds] section, and in place of a bind none of whose
+// shortcuts can be parsed. An action left out of [keybinds] stays unbound. Matches the shipped eldenring_remapper.ini
+pub const DEFAULT_KEYBINDS: &[(&str, &str)] = &[
+    ("memory_slot_1", "Shift+1"),
+    ("memory_slot_2", "Shift+2"),
+    ("memory_slot_3", "Shift+3"),
+    ("memory_slot_4", "Shift+4"),
+    ("memory_slot_5", "Shift+5"),
+    ("memory_slot_6", "Alt+1"),
+    ("memory_slot_7", "Alt+2"),
+    ("memory_slot_8", "Alt+3"),
+    ("memory_slot_9", "Alt+4"),
+    ("memory_slot_10", "Alt+5"),
+    ("memory_slot_11", "RightShift+1"),
+    ("memory_slot_12", "RightShift+2"),
+    ("cycle_back", "2"),
+    ("cycle_forward", "3"),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    SetMemorySlot(u8),
+    CycleBack,
+    CycleForward,
+    // Cycle through the slots of a group.<name> setting only, bound as cycle_back:<name>
+    CycleGroupBack(String),
+    // Bound as cycle_forward:<name>
+    CycleGroupForward(String),
+    // Selects whichever slot holds the spell with this Magic param ID
+    SelectSpell(i32),
+    // Goes back to the slot selected before the last change, whether the remapper or the game made it
+    SwapToPrevious,
+    NoOp,
+}
+
+// Formats as the ini key the action is bound with
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::SetMemorySlot(slot) => write!(f, "memory_slot_{}", slot),
+            Action::CycleBack => write!(f, "cycle_back"),
+            Action::CycleForward => write!(f, "cycle_forward"),
+            Action::CycleGroupBack(group) => write!(f, "cycle_back:{}", group),
+            Action::CycleGroupForward(group) => write!(f, "cycle_forward:{}", group),
+            Action::SwapToPrevious => write!(f, "swap_to_previous"),
+            Action::SelectSpell(param_id) => write!(f, "select_spell({})", spell_label(*param_id)),
+            Action::NoOp => write!(f, "no_op"),
+        }
+    }
+}
+
+pub const DEFAULT_TAP_MILLISECONDS: u64 = 300;
+pub const DEFAULT_HOLD_MILLISECONDS: u64 = 300;
+pub const DEFAULT_DOUBLE_TAP_MILLISECONDS: u64 = 250;
+
+// When a bind fires relative to its keys going down. Written as a prefix in front of the shortcut,
+// e.g. "release:3" or "hold(500ms):3". The timed ones take an optional duration:
+// tap(max press length), hold(how long to hold) and double(max gap between the two presses)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Trigger {
+    OnPress,
+    OnRelease,
+    WhileHeld,
+    Tap(Duration),
+    Hold(Duration),
+    DoubleTap(Duration),
+}
+
+impl Trigger {
+    // Slots only need selecting once, cycling keeps repeating at the debounce rate while held
+    pub fn default_for(action: &Action) -> Trigger {
+        match action {
+            Action::CycleBack | Action::CycleForward | Action::CycleGroupBack(_) | Action::CycleGroupForward(_) => {
+                Trigger::WhileHeld
+            }
+            Action::SetMemorySlot(_) | Action::SelectSpell(_) | Action::SwapToPrevious | Action::NoOp => Trigger::OnPress,
+        }
+    }
+
+    // Triggers of the same kind compete for the same keys, whatever their durations
+    pub fn is_same_kind(&self, other: &Trigger) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    fn is_timed(&self) -> bool {
+        matches!(self, Trigger::Tap(_) | Trigger::Hold(_) | Trigger::DoubleTap(_))
+    }
+
+    // Whether one input can fire binds with both triggers on the same keys. tap:, hold: and double: each
+    // rule the others out, but press, release and held fire on every press alongside anything else
+    pub fn can_fire_with(&self, other: &Trigger) -> bool {
+        self.is_same_kind(other) || !self.is_timed() || !other.is_timed()
+    }
+}
+
+impl Display for Trigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let timed = |f: &mut std::fmt::Formatter<'_>, name: &str, duration: &Duration, default: u64| {
+            if duration.as_millis() == default as u128 {
+                write!(f, "{}", name)
+            } else {
+                write!(f, "{}({}ms)", name, duration.as_millis())
+            }
+        };
+        match self {
+            Trigger::OnPress => write!(f, "press"),
+            Trigger::OnRelease => write!(f, "release"),
+            Trigger::WhileHeld => write!(f, "held"),
+            Trigger::Tap(duration) => timed(f, "tap", duration, DEFAULT_TAP_MILLISECONDS),
+            Trigger::Hold(duration) => timed(f, "hold", duration, DEFAULT_HOLD_MILLISECONDS),
+            Trigger::DoubleTap(duration) => timed(f, "double", duration, DEFAULT_DOUBLE_TAP_MILLISECONDS),
+        }
+    }
+}
+
+fn parse_milliseconds(text: &str) -> Result<Duration, String> {
+    let digits = text.trim().strip_suffix("ms").unwrap_or(text.trim());
+    match digits.trim().parse::<u64>() {
+        Ok(0) | Err(_) => Err(format!("'{}' is not a duration, use milliseconds like 300ms", text.trim())),
+        Ok(milliseconds) => Ok(Duration::from_millis(milliseconds)),
+    }
+}
+
+impl FromStr for Trigger {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        let (name, duration) = match s.split_once('(') {
+            Some((name, rest)) => {
+                let Some(duration) = rest.strip_suffix(')') else {
+                    return Err(format!("'{}' is missing its closing ')'", s));
+                };
+                (name.trim(), Some(parse_milliseconds(duration)?))
+            }
+            None => (s.as_str(), None),
+        };
+        let timed = |trigger: fn(Duration) -> Trigger, default: u64| {
+            Ok(trigger(duration.unwrap_or(Duration::from_millis(default))))
+        };
+        match (name, duration) {
+            ("press", None) => Ok(Trigger::OnPress),
+            ("release", None) => Ok(Trigger::OnRelease),
+            ("held", None) => Ok(Trigger::WhileHeld),
+            ("press" | "release" | "held", Some(_)) => Err(format!("{} doesn't take a duration", name)),
+            ("tap", _) => timed(Trigger::Tap, DEFAULT_TAP_MILLISECONDS),
+            ("hold", _) => timed(Trigger::Hold, DEFAULT_HOLD_MILLISECONDS),
+            ("double", _) => timed(Trigger::DoubleTap, DEFAULT_DOUBLE_TAP_MILLISECONDS),
+            _ => Err(format!("'{}' is not a trigger, use press, release, held, tap, hold or double", name)),
+        }
+    }
+}
+
+// How a layer's key switches it on. Written as "activate = hold:CapsLock" in a [layer.<name>] section
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayerActivation {
+    // Active while the key is down
+    Hold,
+    // Each press switches it on or off
+    Toggle,
+    // Active for the next bind pressed after it, then switches off
+    OneShot,
+}
+
+impl Display for LayerActivation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LayerActivation::Hold => write!(f, "hold"),
+            LayerActivation::Toggle => write!(f, "toggle"),
+            LayerActivation::OneShot => write!(f, "oneshot"),
+        }
+    }
+}
+
+impl FromStr for LayerActivation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "hold" => Ok(LayerActivation::Hold),
+            "toggle" => Ok(LayerActivation::Toggle),
+            "oneshot" | "one_shot" => Ok(LayerActivation::OneShot),
+            _ => Err(format!("'{}' is not a layer activation, use hold, toggle or oneshot", s.trim())),
+        }
+    }
+}
+
+// What a slot action does when it runs off the end of the attuned spells. Cycling wraps by default
+// and memory_slot_<number> past the last spell clamps by default
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SlotBoundary {
+    // Carries on from the other end
+    Wrap,
+    // Stays on the spell at the end
+    Clamp,
+    // Does nothing, not even showing the HUD
+    Ignore,
+}
+
+impl Display for SlotBoundary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SlotBoundary::Wrap => write!(f, "wrap"),
+            SlotBoundary::Clamp => write!(f, "clamp"),
+            SlotBoundary::Ignore => write!(f, "ignore"),
+        }
+    }
+}
+
+impl FromStr for SlotBoundary {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "wrap" => Ok(SlotBoundary::Wrap),
+            "clamp" => Ok(SlotBoundary::Clamp),
+            "ignore" => Ok(SlotBoundary::Ignore),
+            _ => Err(format!("'{}' is not a slot boundary, use wrap, clamp or ignore", s.trim())),
+        }
+    }
+}
+
+// A [layer.<name>] section. Its binds live in LoadedConfig::keybinds with `layer` set to the name
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Layer {
+    pub name: String,
+    pub activation: LayerActivation,
+    pub shortcut: Shortcut,
+    pub line: usize,
+}
+
+pub const LAYER_SECTION_PREFIX: &str = "layer.";
+
+// The keys that block an exact bind when they are held on top of it. Written as a comma separated
+// list of ini key and modifier names, a generic modifier like Shift counts both sides
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModifierKeys(pub Vec<Keycode>);
+
+impl ModifierKeys {
+    pub fn contains(&self, keycode: &Keycode) -> bool {
+        self.0.contains(keycode)
+    }
+}
+
+impl Default for ModifierKeys {
+    fn default() -> Self {
+        ModifierKeys(vec![
+            Keycode::LControl, Keycode::RControl,
+            Keycode::LShift, Keycode::RShift,
+            Keycode::LAlt, Keycode::RAlt,
+            Keycode::LMeta, Keycode::RMeta,
+        ])
+    }
+}
+
+impl Display for ModifierKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names = self.0.iter().map(|k| k.as_str()).collect::<Vec<_>>();
+        write!(f, "{}", names.join(", "))
+    }
+}
+
+impl FromStr for ModifierKeys {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut keycodes = Vec::new();
+        for name in s.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            if let Ok(keycode) = name.parse::<Keycode>() {
+                keycodes.push(keycode);
+            } else if let Ok(modifier) = parse_modifier_with_aliases(name) {
+                let (base_modifier, add_modifier) = map_modifier(&modifier);
+                keycodes.push(base_modifier);
+                keycodes.extend(add_modifier);
+            } else {
+                let keycode = name.parse::<Shortcut>().ok()
+                    .filter(|shortcut| shortcut.modifiers.is_empty() && shortcut.buttons.is_empty())
+                    .ok_or_else(|| format!("'{}' is not a key", name))?;
+                keycodes.push(keycode.key);
+            }
+        }
+        Ok(ModifierKeys(keycodes))
+    }
+}
+
+// A group.<name> setting, the slots its cycle_back:<name> and cycle_forward:<name> binds go through
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SlotGroup {
+    pub name: String,
+    // Numbered from 1 like memory_slot_<number>, in the order they are cycled through
+    pub slots: Vec<u8>,
+    pub line: usize,
+}
+
+impl SlotGroup {
+    pub fn format_slots(&self) -> String {
+        self.slots.iter().map(u8::to_string).collect::<Vec<String>>().join(", ")
+    }
+}
+
+#[derive(Clone)]
+pub struct OtherSettings {
+    pub cycle_debouce_milliseconds: u64,
+    pub sequence_timeout_milliseconds: u64,
+    pub exact_modifiers: bool,
+    pub modifier_keys: ModifierKeys,
+    // Key names mean US keyboard positions instead of whatever the layout prints on the key
+    pub physical_keys: bool,
+    pub record_input: bool,
+    pub cycle_forward_at_end: SlotBoundary,
+    pub cycle_back_at_start: SlotBoundary,
+    pub memory_slot_past_end: SlotBoundary,
+    // memory_slot_<number> on an empty slot does nothing instead of picking the closest spell before it
+    pub ignore_empty_memory_slots: bool,
+    pub groups: Vec<SlotGroup>,
+}
+
+impl Default for OtherSettings {
+    fn default() -> Self {
+        OtherSettings {
+            cycle_debouce_milliseconds: DEFAULT_CYCLE_DEBOUNCE_MILLISECONDS,
+            sequence_timeout_milliseconds: DEFAULT_SEQUENCE_TIMEOUT_MILLISECONDS,
+            exact_modifiers: false,
+            modifier_keys: ModifierKeys::default(),
+            physical_keys: false,
+            record_input: false,
+            cycle_forward_at_end: SlotBoundary::Wrap,
+            cycle_back_at_start: SlotBoundary::Wrap,
+            memory_slot_past_end: SlotBoundary::Clamp,
+            ignore_empty_memory_slots: false,
+            groups: Vec::new(),
+        }
+    }
+}
+
+// A single shortcut bound to an action. An ini entry listing several shortcuts, or a key repeated
+// on several lines, gives one Keybind each. `line` is None for binds that came from DEFAULT_KEYBINDS
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Keybind {
+    // The steps of a sequence like "G > 4" before the last one, empty for a plain shortcut
+    pub leader: Vec<Shortcut>,
+    pub shortcut: Shortcut,
+    pub action: Action,
+    pub trigger: Trigger,
+    // Some(true) for "exact:", Some(false) for "loose:", None to follow the exact_modifiers setting
+    pub exact_modifiers: Option<bool>,
+    // The [layer.<name>] section the bind was written in, None for [keybinds]
+    pub layer: Option<String>,
+    // From a trailing "priority=N", higher wins when several binds match. 0 when not given
+    pub priority: i32,
+    pub ini_key: String,
+    pub line: Option<usize>,
+}
+
+impl Keybind {
+    // The shortcut as it would be written in the ini, with the trigger prefix only when it isn't the default
+    pub fn format_bind(&self) -> String {
+        let steps = self.format_steps(|_| None);
+        let steps = if self.trigger == Trigger::default_for(&self.action) {
+            steps
+        } else {
+            format!("{}:{}", self.trigger, steps)
+        };
+        let steps = match self.exact_modifiers {
+            Some(true) => format!("exact:{}", steps),
+            Some(false) => format!("loose:{}", steps),
+            None => steps,
+        };
+        if self.priority == 0 {
+            steps
+        } else {
+            format!("{} priority={}", steps, self.priority)
+        }
+    }
+
+    // The shortcuts of every step joined with " > ", see Shortcut::format_with for `key_label`
+    pub fn format_steps(&self, key_label: impl Fn(Keycode) -> Option<String>) -> String {
+        self.leader.iter()
+            .chain(std::iter::once(&self.shortcut))
+            .map(|shortcut| shortcut.format_with(&key_label))
+            .collect::<Vec<String>>()
+            .join(" > ")
+    }
+
+    // "memory_slot_1 (Shift+1, line 21)", for naming a bind in the log
+    pub fn describe(&self) -> String {
+        let key = match &self.layer {
+            Some(layer) => format!("{} in [{}{}]", self.ini_key, LAYER_SECTION_PREFIX, layer),
+            None => self.ini_key.clone(),
+        };
+        match self.line {
+            Some(line) => format!("{} ({}, line {})", key, self.format_bind(), line),
+            None => format!("{} ({}, default)", key, self.format_bind()),
+        }
+    }
+}
+
+pub struct LoadedConfig {
+    pub keybinds: Vec<Keybind>,
+    pub layers: Vec<Layer>,
+    pub other_settings: OtherSettings,
+    pub config_version: u32,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl LoadedConfig {
+    pub fn is_outdated(&self) -> bool {
+        self.config_version < CONFIG_VERSION
+    }
+}
+
+pub fn config_key_to_action(key: &str) -> Result<Action, String> {
+    if let Some((direction, group)) = key.split_once(':') {
+        let group = group.trim();
+        if group.is_empty() {
+            return Err(format!("{} needs a group name after the colon", direction.trim()));
+        }
+        return match direction.trim() {
+            "cycle_back" => Ok(Action::CycleGroupBack(group.to_string())),
+            "cycle_forward" => Ok(Action::CycleGroupForward(group.to_string())),
+            _ => Ok(Action::NoOp),
+        };
+    }
+    if let Some(spell) = key.strip_prefix("select_spell") {
+        return match spell.trim().strip_prefix('(').and_then(|s| s.strip_suffix(')')) {
+            Some(spell) => parse_spell(spell).map(Action::SelectSpell),
+            None => Err("select_spell needs a spell, e.g. select_spell(Rock Sling)".to_string()),
+        };
+    }
+
+    match key.strip_prefix("memory_slot_") {
+        Some(s) => {
+            match s.parse::<u8>() {
+                Ok(0) => Err("memory slots are numbered from 1".to_string()),
+                Ok(slot) => Ok(Action::SetMemorySlot(slot)),
+                Err(_) => Err(format!("'{}' is not a memory slot number", s)),
+            }
+        }
+        None => {
+            if key.contains("cycle_back") {
+                return Ok(Action::CycleBack);
+            } else if key.contains("cycle_forward") {
+                return Ok(Action::CycleForward);
+            } else if key == "swap_to_previous" {
+                return Ok(Action::SwapToPrevious);
+            }
+            Ok(Action::NoOp)
+        }
+    }
+}
+
+fn default_shortcut(key: &str) -> Option<(&'static str, Shortcut)> {
+    DEFAULT_KEYBINDS.iter()
+        .find(|(k, _)| *k == key)
+        .and_then(|(_, v)| Some((*v, v.parse().ok()?)))
+}
+
+fn default_keybinds() -> Vec<Keybind> {
+    DEFAULT_KEYBINDS.iter()
+        .filter_map(|(k, v)| {
+            let action = config_key_to_action(k).ok()?;
+            Some(Keybind {
+                leader: Vec::new(),
+                shortcut: v.parse().ok()?,
+                trigger: Trigger::default_for(&action),
+                action,
+                exact_modifiers: None,
+                layer: None,
+                priority: 0,
+                ini_key: k.to_string(),
+                line: None,
+            })
+        })
+        .collect()
+}
+
+// What one item of a keybind value says, before it is tied to an ini entry
+struct ParsedBind {
+    trigger: Trigger,
+    exact_modifiers: Option<bool>,
+    priority: i32,
+    leader: Vec<Shortcut>,
+    shortcut: Shortcut,
+}
+
+// "release:Shift+1", "exact:Shift+1", "G > 4" or a plain "Shift+1", which gets the action's default trigger.
+// Prefixes can be combined like "exact:release:Shift+1", and "Shift+1 priority=5" raises its precedence
+fn parse_bind(text: &str, action: &Action) -> Result<ParsedBind, String> {
+    let (text, priority) = match text.rsplit_once(char::is_whitespace) {
+        Some((rest, attribute)) if attribute.to_lowercase().starts_with("priority=") => {
+            let value = &attribute["priority=".len()..];
+            let priority = value.parse::<i32>().map_err(|_| format!("'{}' is not a priority, use a whole number", value))?;
+            (rest.trim(), priority)
+        }
+        _ => (text, 0),
+    };
+
+    let mut trigger = None;
+    let mut exact_modifiers = None;
+    let mut steps = text;
+    while let Some((prefix, rest)) = steps.split_once(':').filter(|(prefix, _)| !prefix.contains('+')) {
+        match prefix.trim().to_lowercase().as_str() {
+            "exact" => exact_modifiers = Some(true),
+            "loose" => exact_modifiers = Some(false),
+            _ => trigger = Some(prefix.parse::<Trigger>()?),
+        }
+        steps = rest.trim();
+    }
+    let trigger = trigger.unwrap_or(Trigger::default_for(action));
+
+    let mut leader = steps.split('>')
+        .map(str::parse::<Shortcut>)
+        .collect::<Result<Vec<Shortcut>, String>>()?;
+    let shortcut = leader.pop().ok_or_else(|| "no shortcut given".to_string())?;
+    if !leader.is_empty() && trigger != Trigger::default_for(action) {
+        return Err("sequences fire when their last step is pressed and can't take a trigger".to_string());
+    }
+    Ok(ParsedBind { trigger, exact_modifiers, priority, leader, shortcut })
+}
+
+// One entry can hold several shortcuts separated by commas, e.g. "Shift+1, Num1"
+fn read_keybind(path: &Path, entry: &IniEntry, layer: Option<&str>, diagnostics: &mut Vec<Diagnostic>) -> Vec<Keybind> {
+    let action = match config_key_to_action(&entry.key) {
+        Ok(Action::NoOp) => {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::UnknownAction, path, Some(entry.line), Some(&entry.key),
+                "not a known action, ignoring it".to_string(),
+            ).with_suggestion("use memory_slot_<number>, select_spell(<spell>), cycle_back, cycle_forward, cycle_back:<group> or swap_to_previous"));
+            return Vec::new();
+        }
+        Ok(action) => action,
+        Err(reason) if entry.key.starts_with("select_spell") => {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::UnknownSpell, path, Some(entry.line), Some(&entry.key),
+                format!("{}, ignoring it", reason),
+            ).with_suggestion("use the spell's English name or its Magic param ID, e.g. select_spell(4640)"));
+            return Vec::new();
+        }
+        Err(reason) if entry.key.contains(':') => {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::UnknownGroup, path, Some(entry.line), Some(&entry.key),
+                format!("{}, ignoring it", reason),
+            ).with_suggestion("use cycle_forward:<group> or cycle_back:<group>"));
+            return Vec::new();
+        }
+        Err(reason) => {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::InvalidMemorySlot, path, Some(entry.line), Some(&entry.key),
+                format!("{}, ignoring it", reason),
+            ).with_suggestion("use memory_slot_1, memory_slot_2, ..."));
+            return Vec::new();
+        }
+    };
+
+    let keybind = |bind: ParsedBind, line: Option<usize>| Keybind {
+        leader: bind.leader,
+        shortcut: bind.shortcut,
+        action: action.clone(),
+        trigger: bind.trigger,
+        exact_modifiers: bind.exact_modifiers,
+        layer: layer.map(str::to_string),
+        priority: bind.priority,
+        ini_key: entry.key.clone(),
+        line,
+    };
+
+    // An empty value is how a bind gets turned off, so it isn't worth a warning
+    let mut keybinds = Vec::new();
+    let mut invalid = Vec::new();
+    for part in entry.value.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+        match parse_bind(part, &action) {
+            Ok(bind) => keybinds.push(keybind(bind, Some(entry.line))),
+            Err(e) => invalid.push((part, e)),
+        }
+    }
+
+    // The default only stands in when nothing else in the list could be read. Layers have no defaults
+    let fallback = if keybinds.is_empty() && layer.is_none() { default_shortcut(&entry.key) } else { None };
+    for (part, e) in &invalid {
+        let reason = match &fallback {
+            Some((default, _)) => format!("'{}' is not a valid shortcut ({}), using the default {}", part, e, default),
+            None => format!("'{}' is not a valid shortcut ({}), ignoring it", part, e),
+        };
+        diagnostics.push(Diagnostic::new(
+            DiagnosticKind::InvalidShortcut, path, Some(entry.line), Some(&entry.key), reason,
+        ).with_suggestion("see the list of available keys and modifiers at the top of the ini"));
+    }
+    if !invalid.is_empty() {
+        keybinds.extend(fallback.map(|(_, shortcut)| keybind(ParsedBind {
+            trigger: Trigger::default_for(&action),
+            exact_modifiers: None,
+            priority: 0,
+            leader: Vec::new(),
+            shortcut,
+        }, None)));
+    }
+    keybinds
+}
+
+// Reads [keybinds] followed by the binds of every layer in `layers`
+pub fn read_keybinds_config(path: &Path, document: &IniDocument, layers: &[Layer], diagnostics: &mut Vec<Diagnostic>) -> Vec<Keybind> {
+    let mut keybinds = match document.section("keybinds") {
+        Some(entries) => entries.iter()
+            .flat_map(|entry| read_keybind(path, entry, None, diagnostics))
+            .collect(),
+        None => {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::MissingSection, path, None, None,
+                "no [keybinds] section, using the default keybinds".to_string(),
+            ));
+            default_keybinds()
+        }
+    };
+
+    for layer in layers {
+        let section = format!("{}{}", LAYER_SECTION_PREFIX, layer.name);
+        let entries = document.section(&section).unwrap_or_default();
+        keybinds.extend(entries.iter()
+            .filter(|entry| entry.key != "activate")
+            .flat_map(|entry| read_keybind(path, entry, Some(&layer.name), diagnostics)));
+    }
+    keybinds
+}
+
+fn read_layer(path: &Path, document: &IniDocument, section: &str, diagnostics: &mut Vec<Diagnostic>) -> Option<Layer> {
+    let name = section.strip_prefix(LAYER_SECTION_PREFIX)?.trim();
+    let section_line = document.section_line(section);
+    if name.is_empty() {
+        diagnostics.push(Diagnostic::new(
+            DiagnosticKind::InvalidLayer, path, section_line, None,
+            format!("[{}] has no layer name, ignoring it", section),
+        ).with_suggestion("name the layer like [layer.numbers]"));
+        return None;
+    }
+    let Some(entry) = document.get(section, "activate") else {
+        diagnostics.push(Diagnostic::new(
+            DiagnosticKind::InvalidLayer, path, section_line, None,
+            format!("[{}] has no activate key, ignoring the layer", section),
+        ).with_suggestion("add a line like activate = hold:CapsLock"));
+        return None;
+    };
+
+    let parsed = match entry.value.split_once(':') {
+        Some((activation, shortcut)) => activation.parse::<LayerActivation>()
+            .and_then(|activation| Ok((activation, shortcut.parse::<Shortcut>()?))),
+        None => Err("expected an activation and a key like hold:CapsLock".to_string()),
+    };
+    match parsed {
+        Ok((activation, shortcut)) => Some(Layer {
+            name: name.to_string(),
+            activation,
+            shortcut,
+            line: entry.line,
+        }),
+        Err(reason) => {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::InvalidLayer, path, Some(entry.line), Some(&entry.key),
+                format!("'{}' is not a valid activation ({}), ignoring the layer", entry.value, reason),
+            ).with_suggestion("use hold:<key>, toggle:<key> or oneshot:<key>"));
+            None
+        }
+    }
+}
+
+pub fn read_layers_config(path: &Path, document: &IniDocument, diagnostics: &mut Vec<Diagnostic>) -> Vec<Layer> {
+    document.section_names()
+        .into_iter()
+        .filter(|section| section.starts_with(LAYER_SECTION_PREFIX))
+        .filter_map(|section| read_layer(path, document, section, diagnostics))
+        .collect()
+}
+
+fn read_setting<T: FromStr + Display>(path: &Path, entry: Option<&IniEntry>, default: T, diagnostics: &mut Vec<Diagnostic>) -> T {
+    let Some(entry) = entry else {
+        return default;
+    };
+    match entry.value.parse::<T>() {
+        Ok(value) => value,
+        Err(_) => {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::InvalidValue, path, Some(entry.line), Some(&entry.key),
+                format!("'{}' is not a valid value, using the default {}", entry.value, default),
+            ));
+            default
+        }
+    }
+}
+
+pub const GROUP_KEY_PREFIX: &str = "group.";
+
+// Reads every group.<name> = 1, 2, 3 entry in [settings]. A repeated group keeps its last line, like any other setting
+fn read_groups_config(path: &Path, document: &IniDocument, diagnostics: &mut Vec<Diagnostic>) -> Vec<SlotGroup> {
+    let mut groups: Vec<SlotGroup> = Vec::new();
+    for entry in document.section("settings").unwrap_or_default() {
+        let Some(name) = entry.key.strip_prefix(GROUP_KEY_PREFIX) else {
+            continue;
+        };
+        let name = name.trim();
+        if name.is_empty() || name.contains(':') {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::InvalidValue, path, Some(entry.line), Some(&entry.key),
+                "not a valid group name, ignoring it".to_string(),
+            ).with_suggestion("name the group after the dot, e.g. group.buffs = 1, 2, 3"));
+            continue;
+        }
+
+        let mut slots = Vec::new();
+        for part in entry.value.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            match part.parse::<u8>() {
+                Ok(slot) if slot > 0 => slots.push(slot),
+                _ => diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::InvalidValue, path, Some(entry.line), Some(&entry.key),
+                    format!("'{}' is not a memory slot number, leaving it out of the group", part),
+                ).with_suggestion("list memory slots numbered from 1, e.g. group.buffs = 1, 2, 3")),
+            }
+        }
+
+        groups.retain(|group| group.name != name);
+        groups.push(SlotGroup { name: name.to_string(), slots, line: entry.line });
+    }
+    groups
+}
+
+// Group binds naming a group that isn't in [settings] are dropped, there's nothing for them to cycle through
+fn check_group_binds(path: &Path, keybinds: &mut Vec<Keybind>, groups: &[SlotGroup], diagnostics: &mut Vec<Diagnostic>) {
+    keybinds.retain(|keybind| {
+        let (Action::CycleGroupBack(group) | Action::CycleGroupForward(group)) = &keybind.action else {
+            return true;
+        };
+        if groups.iter().any(|g| g.name == *group) {
+            return true;
+        }
+        diagnostics.push(Diagnostic::new(
+            DiagnosticKind::UnknownGroup, path, keybind.line, Some(&keybind.ini_key),
+            format!("there is no group named '{}', ignoring it", group),
+        ).with_suggestion(format!("add {}{} = <slots> to [settings]", GROUP_KEY_PREFIX, group)));
+        false
+    });
+}
+
+pub fn read_other_settings_config(path: &Path, document: &IniDocument, diagnostics: &mut Vec<Diagnostic>) -> OtherSettings {
+    document.report_duplicate_keys(path, "settings", diagnostics);
+    let defaults = OtherSettings::default();
+    OtherSettings {
+        cycle_debouce_milliseconds: read_setting(
+            path,
+            document.get("settings", "cycle_debounce_milliseconds"),
+            defaults.cycle_debouce_milliseconds,
+            diagnostics,
+        ),
+        sequence_timeout_milliseconds: read_setting(
+            path,
+            document.get("settings", "sequence_timeout_milliseconds"),
+            defaults.sequence_timeout_milliseconds,
+            diagnostics,
+        ),
+        exact_modifiers: read_setting(path, document.get("settings", "exact_modifiers"), defaults.exact_modifiers, diagnostics),
+        modifier_keys: read_setting(path, document.get("settings", "modifier_keys"), defaults.modifier_keys, diagnostics),
+        physical_keys: read_setting(path, document.get("settings", "physical_keys"), defaults.physical_keys, diagnostics),
+        record_input: read_setting(path, document.get("settings", "record_input"), defaults.record_input, diagnostics),
+        cycle_forward_at_end: read_setting(
+            path,
+            document.get("settings", "cycle_forward_at_end"),
+            defaults.cycle_forward_at_end,
+            diagnostics,
+        ),
+        cycle_back_at_start: read_setting(
+            path,
+            document.get("settings", "cycle_back_at_start"),
+            defaults.cycle_back_at_start,
+            diagnostics,
+        ),
+        memory_slot_past_end: read_setting(
+            path,
+            document.get("settings", "memory_slot_past_end"),
+            defaults.memory_slot_past_end,
+            diagnostics,
+        ),
+        ignore_empty_memory_slots: read_setting(
+            path,
+            document.get("settings", "ignore_empty_memory_slots"),
+            defaults.ignore_empty_memory_slots,
+            diagnostics,
+        ),
+        groups: read_groups_config(path, document, diagnostics),
+    }
+}
+
+// Never fails: anything that can't be read is reported in `diagnostics` and replaced by its default
+pub fn load_config(path: &Path) -> LoadedConfig {
+    match fs::read_to_string(path) {
+        Ok(text) => load_config_from_str(path, &text),
+        Err(e) => LoadedConfig {
+            keybinds: default_keybinds(),
+            layers: Vec::new(),
+            other_settings: OtherSettings::default(),
+            config_version: CONFIG_VERSION,
+            diagnostics: vec![Diagnostic::new(
+                DiagnosticKind::UnreadableFile, path, None, None,
+                format!("could not read the config ({}), using the default keybinds and settings", e),
+            ).with_suggestion("put eldenring_remapper.ini next to the dll")],
+        },
+    }
+}
+
+// `path` is only used to name the file in the diagnostics
+pub fn load_config_from_str(path: &Path, text: &str) -> LoadedConfig {
+    let mut diagnostics = Vec::new();
+    let mut document = IniDocument::parse(path, text, &mut diagnostics);
+
+    let config_version = migrate_document(path, &mut document, &mut diagnostics);
+    let layers = read_layers_config(path, &document, &mut diagnostics);
+    let mut keybinds = read_keybinds_config(path, &document, &layers, &mut diagnostics);
+    let other_settings = read_other_settings_config(path, &document, &mut diagnostics);
+    check_group_binds(path, &mut keybinds, &other_settings.groups, &mut diagnostics);
+    diagnostics.extend(find_conflicts(path, &keybinds));
+    diagnostics.sort_by_key(|d| d.line);
+
+    LoadedConfig {
+        keybinds,
+        layers,
+        other_settings,
+        config_version,
+        diagnostics,
+    }
+}
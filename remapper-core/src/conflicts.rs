@@ -0,0 +1,143 @@
+use std::{collections::HashSet, path::Path};
+
+use keyboard_codes::Modifier;
+
+use crate::{
+    bindings::{expand_keybind, format_keys, precedence, Keybinding},
+    config::Keybind,
+    diagnostics::{Diagnostic, DiagnosticKind},
+};
+
+fn is_same_shortcut(a: &Keybind, b: &Keybind) -> bool {
+    let a_modifiers = a.shortcut.modifiers.iter().collect::<HashSet<&Modifier>>();
+    let b_modifiers = b.shortcut.modifiers.iter().collect::<HashSet<&Modifier>>();
+    let a_buttons = a.shortcut.buttons.iter().collect::<HashSet<_>>();
+    let b_buttons = b.shortcut.buttons.iter().collect::<HashSet<_>>();
+    a.shortcut.key == b.shortcut.key && a_modifiers == b_modifiers && a_buttons == b_buttons
+}
+
+fn find_strict_subset<'a>(smaller: &'a [Keybinding], larger: &'a [Keybinding]) -> Option<(&'a Keybinding, &'a Keybinding)> {
+    smaller.iter()
+        .flat_map(|s| larger.iter().map(move |l| (s, l)))
+        .find(|(s, l)| s.keys.len() < l.keys.len() && s.keys.is_subset(&l.keys))
+}
+
+// Why `winner` beats `loser` on the same keys, or None when it only wins by coming first in the ini
+fn precedence_reason(winner: &Keybinding, loser: &Keybinding) -> Option<&'static str> {
+    if winner.priority != loser.priority {
+        Some("it has a higher priority")
+    } else if winner.keys.len() != loser.keys.len() {
+        Some("it uses more keys")
+    } else if winner.specific_modifiers != loser.specific_modifiers {
+        Some("its modifiers are side specific")
+    } else {
+        None
+    }
+}
+
+// Compares every pair of binds for different actions. Binds are expected in ini order, which is
+// also the order build_keybindings breaks full ties in, so `earlier` wins when the precedence is equal
+fn compare(path: &Path, earlier: &Keybind, later: &Keybind, earlier_keys: &[Keybinding], later_keys: &[Keybinding]) -> Option<Diagnostic> {
+    // The same keys can carry binds whose triggers rule each other out, e.g. tap:3 and hold:3
+    let can_fire_together = earlier.trigger.can_fire_with(&later.trigger);
+
+    let overlap = earlier_keys.iter()
+        .flat_map(|e| later_keys.iter().map(move |l| (e, l)))
+        .find(|(e, l)| can_fire_together && e.keys == l.keys);
+    if let Some((earlier_keybinding, later_keybinding)) = overlap {
+        // Every trigger bound to the matched keys gets a look, so precedence doesn't pick between these
+        if !earlier.trigger.is_same_kind(&later.trigger) {
+            return Some(Diagnostic::new(
+                DiagnosticKind::DuplicateShortcut, path, later.line.or(earlier.line), Some(&later.ini_key),
+                format!("fires together with {} on {}, one press runs both", earlier.describe(), format_keys(&later_keybinding.keys)),
+            ).with_suggestion("bind one of them to a different shortcut, or use tap: and hold: to share the keys"));
+        }
+
+        let ((winner, winning), (loser, losing)) = if precedence(later_keybinding) > precedence(earlier_keybinding) {
+            ((later, later_keybinding), (earlier, earlier_keybinding))
+        } else {
+            ((earlier, earlier_keybinding), (later, later_keybinding))
+        };
+        let reason = precedence_reason(winning, losing);
+
+        if is_same_shortcut(earlier, later) {
+            let because = reason.map_or("it comes first".to_string(), str::to_string);
+            return Some(Diagnostic::new(
+                DiagnosticKind::DuplicateShortcut, path, loser.line.or(winner.line), Some(&loser.ini_key),
+                format!("uses the same shortcut as {}, {} wins because {} and this bind never fires", winner.describe(), winner.ini_key, because),
+            ).with_suggestion("bind one of them to a different shortcut"));
+        }
+        return Some(match reason {
+            Some(reason) => Diagnostic::new(
+                DiagnosticKind::OutrankedShortcut, path, loser.line.or(winner.line), Some(&loser.ini_key),
+                format!("is outranked by {} on {} because {}", winner.describe(), format_keys(&losing.keys), reason),
+            ),
+            None => Diagnostic::new(
+                DiagnosticKind::OverlappingShortcut, path, loser.line.or(winner.line), Some(&loser.ini_key),
+                format!("ties with {} on {}, {} wins because it comes first", winner.describe(), format_keys(&losing.keys), winner.ini_key),
+            ).with_suggestion("use a left or right specific modifier like LeftShift on one of them, or add priority="),
+        });
+    }
+
+    let (smaller, larger, smaller_keybinding, larger_keybinding) =
+        if let Some((s, l)) = find_strict_subset(earlier_keys, later_keys) {
+            (earlier, later, s, l)
+        } else if let Some((s, l)) = find_strict_subset(later_keys, earlier_keys) {
+            (later, earlier, s, l)
+        } else {
+            return None;
+        };
+
+    // A priority can make the smaller key set win, and then the larger one can never fire
+    if precedence(smaller_keybinding) > precedence(larger_keybinding) {
+        return Some(Diagnostic::new(
+            DiagnosticKind::OutrankedShortcut, path, larger.line.or(smaller.line), Some(&larger.ini_key),
+            format!("is outranked by {} because it has a higher priority, holding {} fires {} instead", smaller.describe(), format_keys(&larger_keybinding.keys), smaller.ini_key),
+        ));
+    }
+    Some(Diagnostic::new(
+        DiagnosticKind::ShadowedShortcut, path, smaller.line.or(larger.line), Some(&smaller.ini_key),
+        format!("is shadowed by {}, holding {} fires {} instead", larger.describe(), format_keys(&larger_keybinding.keys), larger.ini_key),
+    ))
+}
+
+// Pressing the first step of a sequence is kept away from the plain binds, so a plain bind on the same keys never fires
+fn compare_leader(path: &Path, plain: &Keybind, sequence: &Keybind, plain_keys: &[Keybinding], sequence_keys: &[Keybinding]) -> Option<Diagnostic> {
+    let first_step = sequence_keys.first()?.leader.first()?;
+    let keybinding = plain_keys.iter().find(|k| first_step.contains(&k.keys))?;
+    Some(Diagnostic::new(
+        DiagnosticKind::ShadowedShortcut, path, plain.line.or(sequence.line), Some(&plain.ini_key),
+        format!("is shadowed by {}, pressing {} starts that sequence instead", sequence.describe(), format_keys(&keybinding.keys)),
+    ))
+}
+
+pub fn find_conflicts(path: &Path, keybinds: &[Keybind]) -> Vec<Diagnostic> {
+    // Sorted so the example keys named in the log are the same on every run
+    let expanded = keybinds.iter()
+        .map(|k| {
+            let mut keybindings = expand_keybind(k);
+            keybindings.sort_by_key(|k| format_keys(&k.keys));
+            keybindings
+        })
+        .collect::<Vec<Vec<Keybinding>>>();
+
+    let mut conflicts = Vec::new();
+    for i in 0..keybinds.len() {
+        for j in (i + 1)..keybinds.len() {
+            // Sequences only compete with sequences that share all of their earlier steps and with plain binds
+            // on their first step, and layers are looked at before [keybinds] so binds only compete within the same layer
+            let (a, b) = (&keybinds[i], &keybinds[j]);
+            if a.action == b.action || a.layer != b.layer {
+                continue;
+            }
+            let conflict = match (a.leader.is_empty(), b.leader.is_empty()) {
+                (true, false) => compare_leader(path, a, b, &expanded[i], &expanded[j]),
+                (false, true) => compare_leader(path, b, a, &expanded[j], &expanded[i]),
+                _ if a.leader == b.leader => compare(path, a, b, &expanded[i], &expanded[j]),
+                _ => None,
+            };
+            conflicts.extend(conflict);
+        }
+    }
+    conflicts
+}
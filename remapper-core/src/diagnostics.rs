@@ -0,0 +1,81 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    UnreadableFile,
+    MalformedLine,
+    MissingSection,
+    DeprecatedSection,
+    UnsupportedVersion,
+    DuplicateKey,
+    UnknownAction,
+    InvalidMemorySlot,
+    UnknownSpell,
+    InvalidShortcut,
+    InvalidValue,
+    InvalidLayer,
+    UnknownGroup,
+    DuplicateShortcut,
+    OverlappingShortcut,
+    OutrankedShortcut,
+    ShadowedShortcut,
+}
+
+impl DiagnosticKind {
+    // Shadowing is how modifier combos are meant to work (Shift+2 beating 2), and being outranked is
+    // how the precedence rules are meant to work (RightShift+1 beating Shift+1), so they are only
+    // worth an info line rather than a warning
+    pub fn is_warning(&self) -> bool {
+        !matches!(self, DiagnosticKind::ShadowedShortcut | DiagnosticKind::OutrankedShortcut)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub file: PathBuf,
+    pub line: Option<usize>,
+    pub key: Option<String>,
+    pub reason: String,
+    pub suggestion: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(kind: DiagnosticKind, file: &Path, line: Option<usize>, key: Option<&str>, reason: String) -> Self {
+        Diagnostic {
+            kind,
+            file: file.to_path_buf(),
+            line,
+            key: key.map(str::to_string),
+            reason,
+            suggestion: None,
+        }
+    }
+
+    pub fn with_suggestion(mut self, suggestion: impl Into<String>) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+}
+
+// Formats as "file:line: key: reason (suggestion)" so a log line points straight at the typo
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let file_name = self.file.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+        write!(f, "{}", file_name)?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        if let Some(key) = &self.key {
+            write!(f, ": {}", key)?;
+        }
+        write!(f, ": {}", self.reason)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " ({})", suggestion)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use std::{
+    sync::mpsc::{channel, Receiver, Sender},
+    time::Instant,
+};
+
+use crate::{
+    input::{InputFrame, InputSource},
+    keycode::Keycode,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub keycode: Keycode,
+    pub is_down: bool,
+    pub timestamp: Instant,
+}
+
+// Something that reports presses and releases as they happen, like a low level keyboard hook, instead
+// of being sampled once a frame. `start` hands it the sending end of the channel EventInputSource drains,
+// std's mpsc channel never blocks the sender so the hook can't be held up by the game thread
+pub trait KeyEventHook {
+    fn start(self, events: Sender<KeyEvent>) -> Result<(), String>;
+}
+
+// Replays the events queued since the last frame as one frame per change, stamped with when it happened,
+// so taps and double taps shorter than a game frame are timed the same as at any framerate
+pub struct EventInputSource {
+    events: Receiver<KeyEvent>,
+    held: Vec<Keycode>,
+    // Of the last frame handed out. An event stamped just before that frame can still arrive after it,
+    // and frames never go back in time
+    last_timestamp: Option<Instant>,
+}
+
+impl EventInputSource {
+    pub fn new(events: Receiver<KeyEvent>) -> Self {
+        EventInputSource {
+            events,
+            held: Vec::new(),
+            last_timestamp: None,
+        }
+    }
+
+    pub fn start(hook: impl KeyEventHook) -> Result<Self, String> {
+        let (sender, receiver) = channel();
+        hook.start(sender)?;
+        Ok(EventInputSource::new(receiver))
+    }
+
+    // Held keys repeat their down event, which changes nothing
+    fn apply(&mut self, event: &KeyEvent) {
+        let position = self.held.iter().position(|k| *k == event.keycode);
+        match (event.is_down, position) {
+            (true, None) => self.held.push(event.keycode),
+            (false, Some(index)) => {
+                self.held.remove(index);
+            }
+            _ => {}
+        }
+    }
+
+    fn frame_at(&mut self, timestamp: Instant) -> InputFrame {
+        let timestamp = self.last_timestamp.map_or(timestamp, |last| last.max(timestamp));
+        self.last_timestamp = Some(timestamp);
+        InputFrame { timestamp, pressed_keys: self.held.clone() }
+    }
+}
+
+impl InputSource for EventInputSource {
+    // Only the state right now, use poll_changes to see the presses in between
+    fn poll(&mut self) -> Option<InputFrame> {
+        self.poll_changes().pop()
+    }
+
+    fn poll_changes(&mut self) -> Vec<InputFrame> {
+        let events = self.events.try_iter().collect::<Vec<KeyEvent>>();
+        let mut frames = Vec::new();
+        let mut previous_held = self.held.clone();
+        for (index, event) in events.iter().enumerate() {
+            self.apply(event);
+            // One key is reported under several names at once, like Key1 and SC_0x02, which is one change
+            let is_last_at_timestamp = events.get(index + 1).is_none_or(|next| next.timestamp != event.timestamp);
+            if is_last_at_timestamp && self.held != previous_held {
+                previous_held = self.held.clone();
+                frames.push(self.frame_at(event.timestamp));
+            }
+        }
+        // Ends on the state right now, so holds keep being timed while nothing changes
+        frames.push(self.frame_at(Instant::now()));
+        frames
+    }
+}
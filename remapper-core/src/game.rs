@@ -0,0 +1,15 @@
+use crate::slots::MagicSlots;
+
+// The slice of game state the remapper reads and writes every frame. The dll implements this on
+// top of GameDataMan, the simulator on top of plain structs
+pub trait GameState: MagicSlots {
+    type HudType: Copy + PartialEq;
+
+    const HUD_ON: Self::HudType;
+
+    fn player_hp(&self) -> i32;
+
+    fn hud_type(&self) -> Self::HudType;
+
+    fn set_hud_type(&mut self, hud_type: Self::HudType);
+}
@@ -0,0 +1,156 @@
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    input::{InputFrame, InputSource},
+    keycode::Keycode,
+};
+
+// XInput's trigger threshold, below it a resting trigger can still report a little travel
+pub const TRIGGER_THRESHOLD: u8 = 30;
+
+// The wButtons bits of XINPUT_GAMEPAD
+const BUTTONS: &[(u16, Keycode)] = &[
+    (0x0001, Keycode::DPadUp),
+    (0x0002, Keycode::DPadDown),
+    (0x0004, Keycode::DPadLeft),
+    (0x0008, Keycode::DPadRight),
+    (0x0010, Keycode::Start),
+    (0x0020, Keycode::View),
+    (0x0040, Keycode::LeftStick),
+    (0x0080, Keycode::RightStick),
+    (0x0100, Keycode::LeftBumper),
+    (0x0200, Keycode::RightBumper),
+    (0x1000, Keycode::PadA),
+    (0x2000, Keycode::PadB),
+    (0x4000, Keycode::PadX),
+    (0x8000, Keycode::PadY),
+];
+
+// One reading of a controller, laid out like XINPUT_GAMEPAD so other backends have one shape to fill in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GamepadState {
+    pub buttons: u16,
+    pub left_trigger: u8,
+    pub right_trigger: u8,
+}
+
+impl GamepadState {
+    pub fn pressed_keys(&self) -> Vec<Keycode> {
+        let mut keys = BUTTONS.iter()
+            .filter(|(bit, _)| self.buttons & bit != 0)
+            .map(|(_, keycode)| *keycode)
+            .collect::<Vec<Keycode>>();
+        if self.left_trigger > TRIGGER_THRESHOLD {
+            keys.push(Keycode::LeftTrigger);
+        }
+        if self.right_trigger > TRIGGER_THRESHOLD {
+            keys.push(Keycode::RightTrigger);
+        }
+        keys
+    }
+
+    pub fn press(&mut self, keycode: Keycode) {
+        match keycode {
+            Keycode::LeftTrigger => self.left_trigger = u8::MAX,
+            Keycode::RightTrigger => self.right_trigger = u8::MAX,
+            _ => self.buttons |= button_bit(keycode),
+        }
+    }
+
+    pub fn release(&mut self, keycode: Keycode) {
+        match keycode {
+            Keycode::LeftTrigger => self.left_trigger = 0,
+            Keycode::RightTrigger => self.right_trigger = 0,
+            _ => self.buttons &= !button_bit(keycode),
+        }
+    }
+}
+
+fn button_bit(keycode: Keycode) -> u16 {
+    BUTTONS.iter()
+        .find(|(_, button)| *button == keycode)
+        .map_or(0, |(bit, _)| *bit)
+}
+
+// Where controller state comes from, XInput on Windows. None while no controller is connected
+pub trait GamepadBackend {
+    fn poll(&mut self) -> Option<GamepadState>;
+}
+
+// A controller driven by hand, for testing binds without one plugged in. Clones share the same state,
+// so a test can keep one to press buttons on while the other is polled
+#[derive(Clone, Debug)]
+pub struct FakeGamepad(Arc<Mutex<Option<GamepadState>>>);
+
+impl FakeGamepad {
+    // Starts out connected with nothing pressed
+    pub fn new() -> Self {
+        FakeGamepad(Arc::new(Mutex::new(Some(GamepadState::default()))))
+    }
+
+    // Starts out unplugged, until the first press or set_state
+    pub fn disconnected() -> Self {
+        FakeGamepad(Arc::new(Mutex::new(None)))
+    }
+
+    pub fn set_state(&self, state: Option<GamepadState>) {
+        *self.0.lock().unwrap() = state;
+    }
+
+    pub fn press(&self, keycode: Keycode) {
+        self.0.lock().unwrap().get_or_insert_with(GamepadState::default).press(keycode);
+    }
+
+    pub fn release(&self, keycode: Keycode) {
+        if let Some(state) = self.0.lock().unwrap().as_mut() {
+            state.release(keycode);
+        }
+    }
+}
+
+impl Default for FakeGamepad {
+    fn default() -> Self {
+        FakeGamepad::new()
+    }
+}
+
+impl GamepadBackend for FakeGamepad {
+    fn poll(&mut self) -> Option<GamepadState> {
+        *self.0.lock().unwrap()
+    }
+}
+
+// Adds the pressed controller buttons to the frames of another source, so they go through the same
+// binds, layers and conflict checks as keys
+pub struct GamepadInputSource<S, G> {
+    source: S,
+    gamepad: G,
+}
+
+impl<S: InputSource, G: GamepadBackend> GamepadInputSource<S, G> {
+    pub fn new(source: S, gamepad: G) -> Self {
+        GamepadInputSource { source, gamepad }
+    }
+}
+
+impl<S: InputSource, G: GamepadBackend> InputSource for GamepadInputSource<S, G> {
+    fn poll(&mut self) -> Option<InputFrame> {
+        let mut frame = self.source.poll()?;
+        if let Some(state) = self.gamepad.poll() {
+            frame.pressed_keys.extend(state.pressed_keys());
+        }
+        Some(frame)
+    }
+
+    // The controller is only read once a frame, so its buttons go into every change
+    fn poll_changes(&mut self) -> Vec<InputFrame> {
+        let mut frames = self.source.poll_changes();
+        if let Some(state) = self.gamepad.poll() {
+            let buttons = state.pressed_keys();
+            for frame in &mut frames {
+                frame.pressed_keys.extend_from_slice(&buttons);
+            }
+        }
+        frames
+    }
+}
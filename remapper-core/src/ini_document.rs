@@ -0,0 +1,140 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::diagnostics::{Diagnostic, DiagnosticKind};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IniEntry {
+    pub line: usize,
+    pub key: String,
+    pub value: String,
+}
+
+// A minimal ini reader that, unlike the ini crate, remembers which line every entry came from.
+// Section and key names are lowercased, values are kept as written
+#[derive(Clone, Debug, Default)]
+pub struct IniDocument {
+    sections: HashMap<String, Vec<IniEntry>>,
+    section_lines: HashMap<String, usize>,
+}
+
+fn strip_comment(line: &str) -> &str {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('#') || trimmed.starts_with(';') {
+        return "";
+    }
+    // Inline comments need whitespace in front so "Shift+;" style values survive
+    [" #", " ;", "\t#", "\t;"].iter()
+        .filter_map(|marker| line.find(marker))
+        .min()
+        .map_or(line, |index| &line[..index])
+}
+
+impl IniDocument {
+    pub fn parse(path: &Path, text: &str, diagnostics: &mut Vec<Diagnostic>) -> Self {
+        let mut sections: HashMap<String, Vec<IniEntry>> = HashMap::new();
+        let mut section_lines: HashMap<String, usize> = HashMap::new();
+        let mut current_section: Option<String> = None;
+
+        for (index, raw_line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = strip_comment(raw_line).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[') {
+                let Some(name) = name.strip_suffix(']') else {
+                    diagnostics.push(Diagnostic::new(
+                        DiagnosticKind::MalformedLine, path, Some(line_number), None,
+                        format!("section header '{}' is missing its closing ']'", line),
+                    ));
+                    continue;
+                };
+                let name = name.trim().to_lowercase();
+                sections.entry(name.clone()).or_default();
+                section_lines.entry(name.clone()).or_insert(line_number);
+                current_section = Some(name);
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::MalformedLine, path, Some(line_number), None,
+                    format!("expected 'key = value' but found '{}'", line),
+                ));
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+
+            let Some(section) = &current_section else {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::MalformedLine, path, Some(line_number), Some(&key),
+                    "entry appears before any [section] header".to_string(),
+                ).with_suggestion("move it under [keybinds] or [settings]"));
+                continue;
+            };
+
+            // Repeated keys are all kept, [keybinds] reads them as extra shortcuts for the same action
+            sections.entry(section.clone()).or_default().push(IniEntry {
+                line: line_number,
+                key,
+                value: value.trim().to_string(),
+            });
+        }
+
+        IniDocument { sections, section_lines }
+    }
+
+    pub fn section(&self, name: &str) -> Option<&[IniEntry]> {
+        self.sections.get(name).map(Vec::as_slice)
+    }
+
+    // The last entry wins when a key is repeated
+    pub fn get(&self, section: &str, key: &str) -> Option<&IniEntry> {
+        self.section(section)?.iter().rev().find(|e| e.key == key)
+    }
+
+    // For sections where a key can only hold one value, warns about every entry a later one overrides
+    pub fn report_duplicate_keys(&self, path: &Path, section: &str, diagnostics: &mut Vec<Diagnostic>) {
+        let Some(entries) = self.section(section) else {
+            return;
+        };
+        for (index, entry) in entries.iter().enumerate() {
+            if let Some(previous) = entries[..index].iter().rev().find(|e| e.key == entry.key) {
+                diagnostics.push(Diagnostic::new(
+                    DiagnosticKind::DuplicateKey, path, Some(entry.line), Some(&entry.key),
+                    format!("already set on line {}, this later value wins", previous.line),
+                ).with_suggestion("remove one of the two entries"));
+            }
+        }
+    }
+
+    // Every section name in the order they first appear in the file
+    pub fn section_names(&self) -> Vec<&str> {
+        let mut names = self.section_lines.iter().collect::<Vec<(&String, &usize)>>();
+        names.sort_by_key(|(_, line)| **line);
+        names.into_iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    pub fn section_line(&self, name: &str) -> Option<usize> {
+        self.section_lines.get(name).copied()
+    }
+
+    // Moves every entry of `from` into `to`. Entries already in `to` win over the moved ones
+    pub fn rename_section(&mut self, from: &str, to: &str) {
+        let Some(moved) = self.sections.remove(from) else {
+            return;
+        };
+        let line = self.section_lines.remove(from);
+
+        let entries = self.sections.entry(to.to_string()).or_default();
+        for entry in moved {
+            if !entries.iter().any(|e| e.key == entry.key) {
+                entries.push(entry);
+            }
+        }
+        if let Some(line) = line {
+            self.section_lines.entry(to.to_string()).or_insert(line);
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::time::Instant;
+
+use crate::keycode::Keycode;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InputFrame {
+    pub timestamp: Instant,
+    pub pressed_keys: Vec<Keycode>,
+}
+
+pub trait InputSource {
+    // Returns the input state for the next frame, or None once a finite source (like a replay) has
+    // run out of frames
+    fn poll(&mut self) -> Option<InputFrame>;
+
+    // Every change since the last call, oldest first and ending with the state right now. Sources that
+    // see presses and releases as they happen return one frame per change with its own timestamp, so a
+    // tap shorter than a game frame keeps both its press and its release. Polled sources have just the one
+    fn poll_changes(&mut self) -> Vec<InputFrame> {
+        self.poll().into_iter().collect()
+    }
+}
+
+impl<S: InputSource + ?Sized> InputSource for Box<S> {
+    fn poll(&mut self) -> Option<InputFrame> {
+        (**self).poll()
+    }
+
+    fn poll_changes(&mut self) -> Vec<InputFrame> {
+        (**self).poll_changes()
+    }
+}
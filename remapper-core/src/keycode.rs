@@ -0,0 +1,536 @@
+use std::{borrow::Cow, fmt, str::FromStr};
+
+// Started out mirroring the non-macOS variants of device_query::Keycode so the core crate does not have
+// to link against the platform input libraries, and has since grown every key Windows has a name for
+#[derive(Debug, Eq, PartialEq, Hash, Clone, Copy)]
+pub enum Keycode {
+    Key0,
+    Key1,
+    Key2,
+    Key3,
+    Key4,
+    Key5,
+    Key6,
+    Key7,
+    Key8,
+    Key9,
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    S,
+    T,
+    U,
+    V,
+    W,
+    X,
+    Y,
+    Z,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    F13,
+    F14,
+    F15,
+    F16,
+    F17,
+    F18,
+    F19,
+    F20,
+    Escape,
+    Space,
+    LControl,
+    RControl,
+    LShift,
+    RShift,
+    LAlt,
+    RAlt,
+    LMeta,
+    RMeta,
+    Enter,
+    Up,
+    Down,
+    Left,
+    Right,
+    Backspace,
+    CapsLock,
+    Tab,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Insert,
+    Delete,
+    Numpad0,
+    Numpad1,
+    Numpad2,
+    Numpad3,
+    Numpad4,
+    Numpad5,
+    Numpad6,
+    Numpad7,
+    Numpad8,
+    Numpad9,
+    NumpadSubtract,
+    NumpadAdd,
+    NumpadDivide,
+    NumpadMultiply,
+    NumpadEquals,
+    NumpadEnter,
+    NumpadDecimal,
+    Grave,
+    Minus,
+    Equal,
+    LeftBracket,
+    RightBracket,
+    BackSlash,
+    Semicolon,
+    Apostrophe,
+    Comma,
+    Dot,
+    Slash,
+    F21,
+    F22,
+    F23,
+    F24,
+    NumLock,
+    ScrollLock,
+    Pause,
+    PrintScreen,
+    Apps,
+    Sleep,
+    MediaPlayPause,
+    MediaStop,
+    MediaNext,
+    MediaPrevious,
+    VolumeUp,
+    VolumeDown,
+    VolumeMute,
+    BrowserBack,
+    BrowserForward,
+    BrowserRefresh,
+    BrowserHome,
+    // The extra key next to the left Shift on ISO keyboards, '<' on most European layouts
+    Oem102,
+    // Keys without a name above, by Windows virtual-key code, and physical keys by their scan code.
+    // Extended scan codes carry the 0xE0 prefix in the high byte, the right Control key is 0xE01D
+    VirtualKey(u8),
+    ScanCode(u16),
+    // Not keys, but bound and matched the same way. The wheel ones are down for a single frame per notch
+    Mouse4,
+    Mouse5,
+    WheelUp,
+    WheelDown,
+    // Controller buttons, written with their Xbox names. The triggers count as pressed past a threshold
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    Start,
+    View,
+    LeftStick,
+    RightStick,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    PadA,
+    PadB,
+    PadX,
+    PadY,
+}
+
+impl Keycode {
+    pub fn is_modifier(&self) -> bool {
+        matches!(
+            self,
+            Keycode::LControl | Keycode::RControl |
+            Keycode::LShift | Keycode::RShift |
+            Keycode::LAlt | Keycode::RAlt |
+            Keycode::LMeta | Keycode::RMeta |
+            // Shift, Control, Alt and Windows by position, so pressing one never counts as a key press
+            Keycode::ScanCode(0x2A | 0x36 | 0x1D | 0xE01D | 0x38 | 0xE038 | 0xE05B | 0xE05C)
+        )
+    }
+
+    pub fn is_gamepad_button(&self) -> bool {
+        matches!(
+            self,
+            Keycode::DPadUp | Keycode::DPadDown | Keycode::DPadLeft | Keycode::DPadRight |
+            Keycode::Start | Keycode::View |
+            Keycode::LeftStick | Keycode::RightStick |
+            Keycode::LeftBumper | Keycode::RightBumper |
+            Keycode::LeftTrigger | Keycode::RightTrigger |
+            Keycode::PadA | Keycode::PadB | Keycode::PadX | Keycode::PadY
+        )
+    }
+
+    pub fn as_str(&self) -> Cow<'static, str> {
+        let name = match self {
+            Keycode::Key0 => { "Key0" }
+            Keycode::Key1 => { "Key1" }
+            Keycode::Key2 => { "Key2" }
+            Keycode::Key3 => { "Key3" }
+            Keycode::Key4 => { "Key4" }
+            Keycode::Key5 => { "Key5" }
+            Keycode::Key6 => { "Key6" }
+            Keycode::Key7 => { "Key7" }
+            Keycode::Key8 => { "Key8" }
+            Keycode::Key9 => { "Key9" }
+            Keycode::A => { "A" }
+            Keycode::B => { "B" }
+            Keycode::C => { "C" }
+            Keycode::D => { "D" }
+            Keycode::E => { "E" }
+            Keycode::F => { "F" }
+            Keycode::G => { "G" }
+            Keycode::H => { "H" }
+            Keycode::I => { "I" }
+            Keycode::J => { "J" }
+            Keycode::K => { "K" }
+            Keycode::L => { "L" }
+            Keycode::M => { "M" }
+            Keycode::N => { "N" }
+            Keycode::O => { "O" }
+            Keycode::P => { "P" }
+            Keycode::Q => { "Q" }
+            Keycode::R => { "R" }
+            Keycode::S => { "S" }
+            Keycode::T => { "T" }
+            Keycode::U => { "U" }
+            Keycode::V => { "V" }
+            Keycode::W => { "W" }
+            Keycode::X => { "X" }
+            Keycode::Y => { "Y" }
+            Keycode::Z => { "Z" }
+            Keycode::F1 => { "F1" }
+            Keycode::F2 => { "F2" }
+            Keycode::F3 => { "F3" }
+            Keycode::F4 => { "F4" }
+            Keycode::F5 => { "F5" }
+            Keycode::F6 => { "F6" }
+            Keycode::F7 => { "F7" }
+            Keycode::F8 => { "F8" }
+            Keycode::F9 => { "F9" }
+            Keycode::F10 => { "F10" }
+            Keycode::F11 => { "F11" }
+            Keycode::F12 => { "F12" }
+            Keycode::F13 => { "F13" }
+            Keycode::F14 => { "F14" }
+            Keycode::F15 => { "F15" }
+            Keycode::F16 => { "F16" }
+            Keycode::F17 => { "F17" }
+            Keycode::F18 => { "F18" }
+            Keycode::F19 => { "F19" }
+            Keycode::F20 => { "F20" }
+            Keycode::Escape => { "Escape" }
+            Keycode::Space => { "Space" }
+            Keycode::LControl => { "LControl" }
+            Keycode::RControl => { "RControl" }
+            Keycode::LShift => { "LShift" }
+            Keycode::RShift => { "RShift" }
+            Keycode::LAlt => { "LAlt" }
+            Keycode::RAlt => { "RAlt" }
+            Keycode::LMeta => { "LMeta" }
+            Keycode::RMeta => { "RMeta" }
+            Keycode::Enter => { "Enter" }
+            Keycode::Up => { "Up" }
+            Keycode::Down => { "Down" }
+            Keycode::Left => { "Left" }
+            Keycode::Right => { "Right" }
+            Keycode::Backspace => { "Backspace" }
+            Keycode::CapsLock => { "CapsLock" }
+            Keycode::Tab => { "Tab" }
+            Keycode::Home => { "Home" }
+            Keycode::End => { "End" }
+            Keycode::PageUp => { "PageUp" }
+            Keycode::PageDown => { "PageDown" }
+            Keycode::Insert => { "Insert" }
+            Keycode::Delete => { "Delete" }
+            Keycode::Numpad0 => { "Numpad0" }
+            Keycode::Numpad1 => { "Numpad1" }
+            Keycode::Numpad2 => { "Numpad2" }
+            Keycode::Numpad3 => { "Numpad3" }
+            Keycode::Numpad4 => { "Numpad4" }
+            Keycode::Numpad5 => { "Numpad5" }
+            Keycode::Numpad6 => { "Numpad6" }
+            Keycode::Numpad7 => { "Numpad7" }
+            Keycode::Numpad8 => { "Numpad8" }
+            Keycode::Numpad9 => { "Numpad9" }
+            Keycode::NumpadSubtract => { "NumpadSubtract" }
+            Keycode::NumpadAdd => { "NumpadAdd" }
+            Keycode::NumpadDivide => { "NumpadDivide" }
+            Keycode::NumpadMultiply => { "NumpadMultiply" }
+            Keycode::NumpadEquals => { "NumpadEquals" }
+            Keycode::NumpadEnter => { "NumpadEnter" }
+            Keycode::NumpadDecimal => { "NumpadDecimal" }
+            Keycode::Grave => { "Grave" }
+            Keycode::Minus => { "Minus" }
+            Keycode::Equal => { "Equal" }
+            Keycode::LeftBracket => { "LeftBracket" }
+            Keycode::RightBracket => { "RightBracket" }
+            Keycode::BackSlash => { "BackSlash" }
+            Keycode::Semicolon => { "Semicolon" }
+            Keycode::Apostrophe => { "Apostrophe" }
+            Keycode::Comma => { "Comma" }
+            Keycode::Dot => { "Dot" }
+            Keycode::Slash => { "Slash" }
+            Keycode::F21 => { "F21" }
+            Keycode::F22 => { "F22" }
+            Keycode::F23 => { "F23" }
+            Keycode::F24 => { "F24" }
+            Keycode::NumLock => { "NumLock" }
+            Keycode::ScrollLock => { "ScrollLock" }
+            Keycode::Pause => { "Pause" }
+            Keycode::PrintScreen => { "PrintScreen" }
+            Keycode::Apps => { "Apps" }
+            Keycode::Sleep => { "Sleep" }
+            Keycode::MediaPlayPause => { "MediaPlayPause" }
+            Keycode::MediaStop => { "MediaStop" }
+            Keycode::MediaNext => { "MediaNext" }
+            Keycode::MediaPrevious => { "MediaPrevious" }
+            Keycode::VolumeUp => { "VolumeUp" }
+            Keycode::VolumeDown => { "VolumeDown" }
+            Keycode::VolumeMute => { "VolumeMute" }
+            Keycode::BrowserBack => { "BrowserBack" }
+            Keycode::BrowserForward => { "BrowserForward" }
+            Keycode::BrowserRefresh => { "BrowserRefresh" }
+            Keycode::BrowserHome => { "BrowserHome" }
+            Keycode::Oem102 => { "Oem102" }
+            Keycode::VirtualKey(code) => { return format!("VK_0x{:02X}", code).into() }
+            Keycode::ScanCode(code) => { return format!("SC_0x{:02X}", code).into() }
+            Keycode::Mouse4 => { "Mouse4" }
+            Keycode::Mouse5 => { "Mouse5" }
+            Keycode::WheelUp => { "WheelUp" }
+            Keycode::WheelDown => { "WheelDown" }
+            Keycode::DPadUp => { "DPadUp" }
+            Keycode::DPadDown => { "DPadDown" }
+            Keycode::DPadLeft => { "DPadLeft" }
+            Keycode::DPadRight => { "DPadRight" }
+            Keycode::Start => { "Start" }
+            Keycode::View => { "View" }
+            Keycode::LeftStick => { "LS" }
+            Keycode::RightStick => { "RS" }
+            Keycode::LeftBumper => { "LB" }
+            Keycode::RightBumper => { "RB" }
+            Keycode::LeftTrigger => { "LT" }
+            Keycode::RightTrigger => { "RT" }
+            Keycode::PadA => { "PadA" }
+            Keycode::PadB => { "PadB" }
+            Keycode::PadX => { "PadX" }
+            Keycode::PadY => { "PadY" }
+        };
+        name.into()
+    }
+}
+
+impl fmt::Display for Keycode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for Keycode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Key0" => { Ok(Keycode::Key0) }
+            "Key1" => { Ok(Keycode::Key1) }
+            "Key2" => { Ok(Keycode::Key2) }
+            "Key3" => { Ok(Keycode::Key3) }
+            "Key4" => { Ok(Keycode::Key4) }
+            "Key5" => { Ok(Keycode::Key5) }
+            "Key6" => { Ok(Keycode::Key6) }
+            "Key7" => { Ok(Keycode::Key7) }
+            "Key8" => { Ok(Keycode::Key8) }
+            "Key9" => { Ok(Keycode::Key9) }
+            "A" => { Ok(Keycode::A) }
+            "B" => { Ok(Keycode::B) }
+            "C" => { Ok(Keycode::C) }
+            "D" => { Ok(Keycode::D) }
+            "E" => { Ok(Keycode::E) }
+            "F" => { Ok(Keycode::F) }
+            "G" => { Ok(Keycode::G) }
+            "H" => { Ok(Keycode::H) }
+            "I" => { Ok(Keycode::I) }
+            "J" => { Ok(Keycode::J) }
+            "K" => { Ok(Keycode::K) }
+            "L" => { Ok(Keycode::L) }
+            "M" => { Ok(Keycode::M) }
+            "N" => { Ok(Keycode::N) }
+            "O" => { Ok(Keycode::O) }
+            "P" => { Ok(Keycode::P) }
+            "Q" => { Ok(Keycode::Q) }
+            "R" => { Ok(Keycode::R) }
+            "S" => { Ok(Keycode::S) }
+            "T" => { Ok(Keycode::T) }
+            "U" => { Ok(Keycode::U) }
+            "V" => { Ok(Keycode::V) }
+            "W" => { Ok(Keycode::W) }
+            "X" => { Ok(Keycode::X) }
+            "Y" => { Ok(Keycode::Y) }
+            "Z" => { Ok(Keycode::Z) }
+            "F1" => { Ok(Keycode::F1) }
+            "F2" => { Ok(Keycode::F2) }
+            "F3" => { Ok(Keycode::F3) }
+            "F4" => { Ok(Keycode::F4) }
+            "F5" => { Ok(Keycode::F5) }
+            "F6" => { Ok(Keycode::F6) }
+            "F7" => { Ok(Keycode::F7) }
+            "F8" => { Ok(Keycode::F8) }
+            "F9" => { Ok(Keycode::F9) }
+            "F10" => { Ok(Keycode::F10) }
+            "F11" => { Ok(Keycode::F11) }
+            "F12" => { Ok(Keycode::F12) }
+            "F13" => { Ok(Keycode::F13) }
+            "F14" => { Ok(Keycode::F14) }
+            "F15" => { Ok(Keycode::F15) }
+            "F16" => { Ok(Keycode::F16) }
+            "F17" => { Ok(Keycode::F17) }
+            "F18" => { Ok(Keycode::F18) }
+            "F19" => { Ok(Keycode::F19) }
+            "F20" => { Ok(Keycode::F20) }
+            "Escape" => { Ok(Keycode::Escape) }
+            "Space" => { Ok(Keycode::Space) }
+            "LControl" => { Ok(Keycode::LControl) }
+            "RControl" => { Ok(Keycode::RControl) }
+            "LShift" => { Ok(Keycode::LShift) }
+            "RShift" => { Ok(Keycode::RShift) }
+            "LAlt" => { Ok(Keycode::LAlt) }
+            "RAlt" => { Ok(Keycode::RAlt) }
+            "LMeta" => { Ok(Keycode::LMeta) }
+            "RMeta" => { Ok(Keycode::RMeta) }
+            "Enter" => { Ok(Keycode::Enter) }
+            "Up" => { Ok(Keycode::Up) }
+            "Down" => { Ok(Keycode::Down) }
+            "Left" => { Ok(Keycode::Left) }
+            "Right" => { Ok(Keycode::Right) }
+            "Backspace" => { Ok(Keycode::Backspace) }
+            "CapsLock" => { Ok(Keycode::CapsLock) }
+            "Tab" => { Ok(Keycode::Tab) }
+            "Home" => { Ok(Keycode::Home) }
+            "End" => { Ok(Keycode::End) }
+            "PageUp" => { Ok(Keycode::PageUp) }
+            "PageDown" => { Ok(Keycode::PageDown) }
+            "Insert" => { Ok(Keycode::Insert) }
+            "Delete" => { Ok(Keycode::Delete) }
+            "Numpad0" => { Ok(Keycode::Numpad0) }
+            "Numpad1" => { Ok(Keycode::Numpad1) }
+            "Numpad2" => { Ok(Keycode::Numpad2) }
+            "Numpad3" => { Ok(Keycode::Numpad3) }
+            "Numpad4" => { Ok(Keycode::Numpad4) }
+            "Numpad5" => { Ok(Keycode::Numpad5) }
+            "Numpad6" => { Ok(Keycode::Numpad6) }
+            "Numpad7" => { Ok(Keycode::Numpad7) }
+            "Numpad8" => { Ok(Keycode::Numpad8) }
+            "Numpad9" => { Ok(Keycode::Numpad9) }
+            "NumpadSubtract" => { Ok(Keycode::NumpadSubtract) }
+            "NumpadAdd" => { Ok(Keycode::NumpadAdd) }
+            "NumpadDivide" => { Ok(Keycode::NumpadDivide) }
+            "NumpadMultiply" => { Ok(Keycode::NumpadMultiply) }
+            "NumpadEquals" => { Ok(Keycode::NumpadEquals) }
+            "NumpadEnter" => { Ok(Keycode::NumpadEnter) }
+            "NumpadDecimal" => { Ok(Keycode::NumpadDecimal) }
+            "Grave" => { Ok(Keycode::Grave) }
+            "Minus" => { Ok(Keycode::Minus) }
+            "Equal" => { Ok(Keycode::Equal) }
+            "LeftBracket" => { Ok(Keycode::LeftBracket) }
+            "RightBracket" => { Ok(Keycode::RightBracket) }
+            "BackSlash" => { Ok(Keycode::BackSlash) }
+            "Semicolon" => { Ok(Keycode::Semicolon) }
+            "Apostrophe" => { Ok(Keycode::Apostrophe) }
+            "Comma" => { Ok(Keycode::Comma) }
+            "Dot" => { Ok(Keycode::Dot) }
+            "Slash" => { Ok(Keycode::Slash) }
+            "F21" => { Ok(Keycode::F21) }
+            "F22" => { Ok(Keycode::F22) }
+            "F23" => { Ok(Keycode::F23) }
+            "F24" => { Ok(Keycode::F24) }
+            "NumLock" => { Ok(Keycode::NumLock) }
+            "ScrollLock" => { Ok(Keycode::ScrollLock) }
+            "Pause" => { Ok(Keycode::Pause) }
+            "PrintScreen" => { Ok(Keycode::PrintScreen) }
+            "Apps" => { Ok(Keycode::Apps) }
+            "Sleep" => { Ok(Keycode::Sleep) }
+            "MediaPlayPause" => { Ok(Keycode::MediaPlayPause) }
+            "MediaStop" => { Ok(Keycode::MediaStop) }
+            "MediaNext" => { Ok(Keycode::MediaNext) }
+            "MediaPrevious" => { Ok(Keycode::MediaPrevious) }
+            "VolumeUp" => { Ok(Keycode::VolumeUp) }
+            "VolumeDown" => { Ok(Keycode::VolumeDown) }
+            "VolumeMute" => { Ok(Keycode::VolumeMute) }
+            "BrowserBack" => { Ok(Keycode::BrowserBack) }
+            "BrowserForward" => { Ok(Keycode::BrowserForward) }
+            "BrowserRefresh" => { Ok(Keycode::BrowserRefresh) }
+            "BrowserHome" => { Ok(Keycode::BrowserHome) }
+            "Oem102" => { Ok(Keycode::Oem102) }
+            "Mouse4" => { Ok(Keycode::Mouse4) }
+            "Mouse5" => { Ok(Keycode::Mouse5) }
+            "WheelUp" => { Ok(Keycode::WheelUp) }
+            "WheelDown" => { Ok(Keycode::WheelDown) }
+            "DPadUp" => { Ok(Keycode::DPadUp) }
+            "DPadDown" => { Ok(Keycode::DPadDown) }
+            "DPadLeft" => { Ok(Keycode::DPadLeft) }
+            "DPadRight" => { Ok(Keycode::DPadRight) }
+            "Start" => { Ok(Keycode::Start) }
+            "View" => { Ok(Keycode::View) }
+            "LS" => { Ok(Keycode::LeftStick) }
+            "RS" => { Ok(Keycode::RightStick) }
+            "LB" => { Ok(Keycode::LeftBumper) }
+            "RB" => { Ok(Keycode::RightBumper) }
+            "LT" => { Ok(Keycode::LeftTrigger) }
+            "RT" => { Ok(Keycode::RightTrigger) }
+            "PadA" => { Ok(Keycode::PadA) }
+            "PadB" => { Ok(Keycode::PadB) }
+            "PadX" => { Ok(Keycode::PadX) }
+            "PadY" => { Ok(Keycode::PadY) }
+            _ => {
+                parse_raw_code(s)
+                    .ok_or_else(|| format!("Unknown keycode '{}'", s))
+            }
+        }
+    }
+}
+
+fn parse_hex<T: TryFrom<u32>>(s: &str, prefix: &str) -> Option<T> {
+    let digits = s.get(..prefix.len())
+        .filter(|start| start.eq_ignore_ascii_case(prefix))
+        .map(|_| &s[prefix.len()..])?;
+    let digits = digits.strip_prefix("0x").or_else(|| digits.strip_prefix("0X")).unwrap_or(digits);
+    u32::from_str_radix(digits, 16).ok().and_then(|code| T::try_from(code).ok())
+}
+
+// "VK_0x7C" and "SC_0x1A", either case and with or without the 0x. Virtual-key codes 0x00 and 0xFF
+// and scan codes outside the one byte and 0xE0 prefixed ranges are never reported
+pub fn parse_raw_code(s: &str) -> Option<Keycode> {
+    if let Some(code) = parse_hex::<u8>(s, "VK_") {
+        return (code != 0x00 && code != 0xFF).then_some(Keycode::VirtualKey(code));
+    }
+    parse_hex::<u16>(s, "SC_")
+        .filter(|code| code & 0xFF != 0 && matches!(code >> 8, 0x00 | 0xE0))
+        .map(Keycode::ScanCode)
+}
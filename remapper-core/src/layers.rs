@@ -0,0 +1,66 @@
+use crate::{
+    bindings::{is_all_keybinding_keys_pressed, LayerBinding},
+    config::LayerActivation,
+    keycode::Keycode,
+};
+
+// Which layers are switched on. Their binds are looked at before [keybinds], the most recently
+// switched on layer first, and anything a layer doesn't bind falls through to the layers below
+pub struct LayerStack {
+    layers: Vec<LayerBinding>,
+    // Indices into `layers`, the most recently switched on last
+    active: Vec<usize>,
+    previous_pressed: Vec<Keycode>,
+}
+
+impl LayerStack {
+    pub fn new(layers: Vec<LayerBinding>) -> Self {
+        LayerStack {
+            layers,
+            active: Vec::new(),
+            previous_pressed: Vec::new(),
+        }
+    }
+
+    // Layers are matched by index, so a reload switches every layer off
+    pub fn reload(&mut self, layers: Vec<LayerBinding>) {
+        self.layers = layers;
+        self.active.clear();
+    }
+
+    // Applies this frame's presses and releases of the layer keys
+    pub fn update(&mut self, pressed_keys: &[Keycode]) {
+        let previous_pressed = std::mem::replace(&mut self.previous_pressed, pressed_keys.to_vec());
+
+        for index in 0..self.layers.len() {
+            let layer = &self.layers[index];
+            let is_down = layer.keys.iter().any(|keys| is_all_keybinding_keys_pressed(keys, pressed_keys));
+            let was_down = layer.keys.iter().any(|keys| is_all_keybinding_keys_pressed(keys, &previous_pressed));
+            let is_active = self.active.contains(&index);
+
+            let is_switching = match layer.activation {
+                LayerActivation::Hold => is_down != is_active,
+                LayerActivation::Toggle | LayerActivation::OneShot => is_down && !was_down,
+            };
+            if !is_switching {
+                continue;
+            }
+            if is_active {
+                self.active.retain(|i| *i != index);
+            } else {
+                self.active.push(index);
+            }
+        }
+    }
+
+    // Most recently switched on first
+    pub fn active_layers(&self) -> Vec<String> {
+        self.active.iter().rev().map(|i| self.layers[*i].name.clone()).collect()
+    }
+
+    // Called once a bind was pressed, which uses up any one-shot layer
+    pub fn release_one_shot(&mut self) {
+        let layers = &self.layers;
+        self.active.retain(|i| layers[*i].activation != LayerActivation::OneShot);
+    }
+}
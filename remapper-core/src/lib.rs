@@ -0,0 +1,42 @@
+pub mod bindings;
+pub mod config;
+pub mod conflicts;
+pub mod diagnostics;
+pub mod events;
+pub mod game;
+pub mod gamepad;
+pub mod ini_document;
+pub mod input;
+pub mod keycode;
+pub mod layers;
+pub mod mapper;
+pub mod recording;
+pub mod reload;
+pub mod remapper;
+pub mod schema;
+pub mod sequence;
+pub mod shortcut;
+pub mod simple_logger;
+pub mod slots;
+pub mod spells;
+pub mod wheel;
+
+pub use crate::{
+    bindings::{build_keybindings, build_layers, Keybinding, LayerBinding},
+    config::{load_config, load_config_from_str, Action, Keybind, Layer, LayerActivation, LoadedConfig, ModifierKeys, OtherSettings, SlotBoundary, SlotGroup, Trigger},
+    diagnostics::{Diagnostic, DiagnosticKind},
+    events::{EventInputSource, KeyEvent, KeyEventHook},
+    game::GameState,
+    gamepad::{FakeGamepad, GamepadBackend, GamepadInputSource, GamepadState},
+    input::{InputFrame, InputSource},
+    keycode::Keycode,
+    recording::{RecordingInputSource, ReplayInputSource},
+    reload::{describe_config_changes, ConfigWatcher},
+    remapper::Remapper,
+    schema::{rewrite_config_file, CONFIG_VERSION},
+    shortcut::Shortcut,
+    simple_logger::SimpleLogger,
+    slots::MagicSlots,
+    spells::{spell_by_name, spell_by_param_id, spells, Spell, SpellType},
+    wheel::{WheelInputSource, WheelNotches},
+};
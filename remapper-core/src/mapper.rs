@@ -0,0 +1,435 @@
+use crate::keycode::Keycode;
+use keyboard_codes::{Key, Modifier};
+
+pub fn map_key(key: &Key) -> Option<Keycode> {
+    match key {
+        Key::Escape => { Some(Keycode::Escape) }
+        Key::Enter => { Some(Keycode::Enter) }
+        Key::Tab => { Some(Keycode::Tab) }
+        Key::Backspace => { Some(Keycode::Backspace) }
+        Key::Space => { Some(Keycode::Space) }
+        Key::Insert => { Some(Keycode::Insert) }
+        Key::Delete => { Some(Keycode::Delete) }
+        Key::Home => { Some(Keycode::Home) }
+        Key::End => { Some(Keycode::End) }
+        Key::PageUp => { Some(Keycode::PageUp) }
+        Key::PageDown => { Some(Keycode::PageDown) }
+        Key::ArrowLeft => { Some(Keycode::Left) }
+        Key::ArrowUp => { Some(Keycode::Up) }
+        Key::ArrowRight => { Some(Keycode::Right) }
+        Key::ArrowDown => { Some(Keycode::Down) }
+        Key::A => { Some(Keycode::A) }
+        Key::B => { Some(Keycode::B) }
+        Key::C => { Some(Keycode::C) }
+        Key::D => { Some(Keycode::D) }
+        Key::E => { Some(Keycode::E) }
+        Key::F => { Some(Keycode::F) }
+        Key::G => { Some(Keycode::G) }
+        Key::H => { Some(Keycode::H) }
+        Key::I => { Some(Keycode::I) }
+        Key::J => { Some(Keycode::J) }
+        Key::K => { Some(Keycode::K) }
+        Key::L => { Some(Keycode::L) }
+        Key::M => { Some(Keycode::M) }
+        Key::N => { Some(Keycode::N) }
+        Key::O => { Some(Keycode::O) }
+        Key::P => { Some(Keycode::P) }
+        Key::Q => { Some(Keycode::Q) }
+        Key::R => { Some(Keycode::R) }
+        Key::S => { Some(Keycode::S) }
+        Key::T => { Some(Keycode::T) }
+        Key::U => { Some(Keycode::U) }
+        Key::V => { Some(Keycode::V) }
+        Key::W => { Some(Keycode::W) }
+        Key::X => { Some(Keycode::X) }
+        Key::Y => { Some(Keycode::Y) }
+        Key::Z => { Some(Keycode::Z) }
+        Key::D0 => { Some(Keycode::Key0) }
+        Key::D1 => { Some(Keycode::Key1) }
+        Key::D2 => { Some(Keycode::Key2) }
+        Key::D3 => { Some(Keycode::Key3) }
+        Key::D4 => { Some(Keycode::Key4) }
+        Key::D5 => { Some(Keycode::Key5) }
+        Key::D6 => { Some(Keycode::Key6) }
+        Key::D7 => { Some(Keycode::Key7) }
+        Key::D8 => { Some(Keycode::Key8) }
+        Key::D9 => { Some(Keycode::Key9) }
+        Key::Num0 => { Some(Keycode::Numpad0) }
+        Key::Num1 => { Some(Keycode::Numpad1) }
+        Key::Num2 => { Some(Keycode::Numpad2) }
+        Key::Num3 => { Some(Keycode::Numpad3) }
+        Key::Num4 => { Some(Keycode::Numpad4) }
+        Key::Num5 => { Some(Keycode::Numpad5) }
+        Key::Num6 => { Some(Keycode::Numpad6) }
+        Key::Num7 => { Some(Keycode::Numpad7) }
+        Key::Num8 => { Some(Keycode::Numpad8) }
+        Key::Num9 => { Some(Keycode::Numpad9) }
+        Key::NumMultiply => { Some(Keycode::NumpadMultiply) }
+        Key::NumAdd => { Some(Keycode::NumpadAdd) }
+        Key::NumSubtract => { Some(Keycode::NumpadSubtract) }
+        Key::NumDivide => { Some(Keycode::NumpadDivide) }
+        Key::NumDecimal => { Some(Keycode::NumpadDecimal) }
+        Key::F1 => { Some(Keycode::F1) }
+        Key::F2 => { Some(Keycode::F2) }
+        Key::F3 => { Some(Keycode::F3) }
+        Key::F4 => { Some(Keycode::F4) }
+        Key::F5 => { Some(Keycode::F5) }
+        Key::F6 => { Some(Keycode::F6) }
+        Key::F7 => { Some(Keycode::F7) }
+        Key::F8 => { Some(Keycode::F8) }
+        Key::F9 => { Some(Keycode::F9) }
+        Key::F10 => { Some(Keycode::F10) }
+        Key::F11 => { Some(Keycode::F11) }
+        Key::F12 => { Some(Keycode::F12) }
+        Key::F13 => { Some(Keycode::F13) }
+        Key::F14 => { Some(Keycode::F14) }
+        Key::F15 => { Some(Keycode::F15) }
+        Key::F16 => { Some(Keycode::F16) }
+        Key::F17 => { Some(Keycode::F17) }
+        Key::F18 => { Some(Keycode::F18) }
+        Key::F19 => { Some(Keycode::F19) }
+        Key::F20 => { Some(Keycode::F20) }
+        Key::F21 => { Some(Keycode::F21) }
+        Key::F22 => { Some(Keycode::F22) }
+        Key::F23 => { Some(Keycode::F23) }
+        Key::F24 => { Some(Keycode::F24) }
+        Key::CapsLock => { Some(Keycode::CapsLock) }
+        Key::NumLock => { Some(Keycode::NumLock) }
+        Key::ScrollLock => { Some(Keycode::ScrollLock) }
+        Key::Pause => { Some(Keycode::Pause) }
+        Key::Apps => { Some(Keycode::Apps) }
+        Key::Sleep => { Some(Keycode::Sleep) }
+        Key::MediaPlayPause => { Some(Keycode::MediaPlayPause) }
+        Key::MediaStop => { Some(Keycode::MediaStop) }
+        Key::MediaNext => { Some(Keycode::MediaNext) }
+        Key::MediaPrevious => { Some(Keycode::MediaPrevious) }
+        Key::VolumeUp => { Some(Keycode::VolumeUp) }
+        Key::VolumeDown => { Some(Keycode::VolumeDown) }
+        Key::VolumeMute => { Some(Keycode::VolumeMute) }
+        Key::BrowserBack => { Some(Keycode::BrowserBack) }
+        Key::BrowserForward => { Some(Keycode::BrowserForward) }
+        Key::BrowserRefresh => { Some(Keycode::BrowserRefresh) }
+        Key::BrowserHome => { Some(Keycode::BrowserHome) }
+    }
+}
+
+// Windows virtual-key codes of every key with a name. Anything else Windows reports is bound as VK_0x<code>
+const VIRTUAL_KEYS: &[(u8, Keycode)] = &[
+    (0x05, Keycode::Mouse4),
+    (0x06, Keycode::Mouse5),
+    (0x08, Keycode::Backspace),
+    (0x09, Keycode::Tab),
+    (0x0D, Keycode::Enter),
+    (0x13, Keycode::Pause),
+    (0x14, Keycode::CapsLock),
+    (0x1B, Keycode::Escape),
+    (0x20, Keycode::Space),
+    (0x21, Keycode::PageUp),
+    (0x22, Keycode::PageDown),
+    (0x23, Keycode::End),
+    (0x24, Keycode::Home),
+    (0x25, Keycode::Left),
+    (0x26, Keycode::Up),
+    (0x27, Keycode::Right),
+    (0x28, Keycode::Down),
+    (0x2C, Keycode::PrintScreen),
+    (0x2D, Keycode::Insert),
+    (0x2E, Keycode::Delete),
+    (0x30, Keycode::Key0),
+    (0x31, Keycode::Key1),
+    (0x32, Keycode::Key2),
+    (0x33, Keycode::Key3),
+    (0x34, Keycode::Key4),
+    (0x35, Keycode::Key5),
+    (0x36, Keycode::Key6),
+    (0x37, Keycode::Key7),
+    (0x38, Keycode::Key8),
+    (0x39, Keycode::Key9),
+    (0x41, Keycode::A),
+    (0x42, Keycode::B),
+    (0x43, Keycode::C),
+    (0x44, Keycode::D),
+    (0x45, Keycode::E),
+    (0x46, Keycode::F),
+    (0x47, Keycode::G),
+    (0x48, Keycode::H),
+    (0x49, Keycode::I),
+    (0x4A, Keycode::J),
+    (0x4B, Keycode::K),
+    (0x4C, Keycode::L),
+    (0x4D, Keycode::M),
+    (0x4E, Keycode::N),
+    (0x4F, Keycode::O),
+    (0x50, Keycode::P),
+    (0x51, Keycode::Q),
+    (0x52, Keycode::R),
+    (0x53, Keycode::S),
+    (0x54, Keycode::T),
+    (0x55, Keycode::U),
+    (0x56, Keycode::V),
+    (0x57, Keycode::W),
+    (0x58, Keycode::X),
+    (0x59, Keycode::Y),
+    (0x5A, Keycode::Z),
+    (0x5B, Keycode::LMeta),
+    (0x5C, Keycode::RMeta),
+    (0x5D, Keycode::Apps),
+    (0x5F, Keycode::Sleep),
+    (0x60, Keycode::Numpad0),
+    (0x61, Keycode::Numpad1),
+    (0x62, Keycode::Numpad2),
+    (0x63, Keycode::Numpad3),
+    (0x64, Keycode::Numpad4),
+    (0x65, Keycode::Numpad5),
+    (0x66, Keycode::Numpad6),
+    (0x67, Keycode::Numpad7),
+    (0x68, Keycode::Numpad8),
+    (0x69, Keycode::Numpad9),
+    (0x6A, Keycode::NumpadMultiply),
+    (0x6B, Keycode::NumpadAdd),
+    (0x6D, Keycode::NumpadSubtract),
+    (0x6E, Keycode::NumpadDecimal),
+    (0x6F, Keycode::NumpadDivide),
+    (0x70, Keycode::F1),
+    (0x71, Keycode::F2),
+    (0x72, Keycode::F3),
+    (0x73, Keycode::F4),
+    (0x74, Keycode::F5),
+    (0x75, Keycode::F6),
+    (0x76, Keycode::F7),
+    (0x77, Keycode::F8),
+    (0x78, Keycode::F9),
+    (0x79, Keycode::F10),
+    (0x7A, Keycode::F11),
+    (0x7B, Keycode::F12),
+    (0x7C, Keycode::F13),
+    (0x7D, Keycode::F14),
+    (0x7E, Keycode::F15),
+    (0x7F, Keycode::F16),
+    (0x80, Keycode::F17),
+    (0x81, Keycode::F18),
+    (0x82, Keycode::F19),
+    (0x83, Keycode::F20),
+    (0x84, Keycode::F21),
+    (0x85, Keycode::F22),
+    (0x86, Keycode::F23),
+    (0x87, Keycode::F24),
+    (0x90, Keycode::NumLock),
+    (0x91, Keycode::ScrollLock),
+    (0xA0, Keycode::LShift),
+    (0xA1, Keycode::RShift),
+    (0xA2, Keycode::LControl),
+    (0xA3, Keycode::RControl),
+    (0xA4, Keycode::LAlt),
+    (0xA5, Keycode::RAlt),
+    (0xA6, Keycode::BrowserBack),
+    (0xA7, Keycode::BrowserForward),
+    (0xA8, Keycode::BrowserRefresh),
+    (0xAC, Keycode::BrowserHome),
+    (0xAD, Keycode::VolumeMute),
+    (0xAE, Keycode::VolumeDown),
+    (0xAF, Keycode::VolumeUp),
+    (0xB0, Keycode::MediaNext),
+    (0xB1, Keycode::MediaPrevious),
+    (0xB2, Keycode::MediaStop),
+    (0xB3, Keycode::MediaPlayPause),
+    (0xBA, Keycode::Semicolon),
+    (0xBB, Keycode::Equal),
+    (0xBC, Keycode::Comma),
+    (0xBD, Keycode::Minus),
+    (0xBE, Keycode::Dot),
+    (0xBF, Keycode::Slash),
+    (0xC0, Keycode::Grave),
+    (0xDB, Keycode::LeftBracket),
+    (0xDC, Keycode::BackSlash),
+    (0xDD, Keycode::RightBracket),
+    (0xDE, Keycode::Apostrophe),
+    (0xE2, Keycode::Oem102),
+];
+
+// Left, right and middle click would fire binds on every attack, and the side-neutral Shift, Control
+// and Alt codes are always down together with the left or right one, which is what gets reported
+fn is_unbindable_virtual_key(code: u8) -> bool {
+    matches!(code, 0x00..=0x04 | 0x10..=0x12 | 0xFF)
+}
+
+// The Keycode input sources report for a virtual-key code, the named one where there is one
+pub fn map_virtual_key(code: u8) -> Option<Keycode> {
+    if is_unbindable_virtual_key(code) {
+        return None;
+    }
+    let named = VIRTUAL_KEYS.iter().find(|(virtual_key, _)| *virtual_key == code);
+    Some(named.map_or(Keycode::VirtualKey(code), |(_, keycode)| *keycode))
+}
+
+// Where each key sits on a US keyboard, by set 1 scan code with the 0xE0 prefix in the high byte.
+// physical_keys = true reads binds by these positions, so "Q" is the key right of Tab on any layout
+const SCAN_CODES: &[(u16, Keycode)] = &[
+    (0x01, Keycode::Escape),
+    (0x02, Keycode::Key1),
+    (0x03, Keycode::Key2),
+    (0x04, Keycode::Key3),
+    (0x05, Keycode::Key4),
+    (0x06, Keycode::Key5),
+    (0x07, Keycode::Key6),
+    (0x08, Keycode::Key7),
+    (0x09, Keycode::Key8),
+    (0x0A, Keycode::Key9),
+    (0x0B, Keycode::Key0),
+    (0x0C, Keycode::Minus),
+    (0x0D, Keycode::Equal),
+    (0x0E, Keycode::Backspace),
+    (0x0F, Keycode::Tab),
+    (0x10, Keycode::Q),
+    (0x11, Keycode::W),
+    (0x12, Keycode::E),
+    (0x13, Keycode::R),
+    (0x14, Keycode::T),
+    (0x15, Keycode::Y),
+    (0x16, Keycode::U),
+    (0x17, Keycode::I),
+    (0x18, Keycode::O),
+    (0x19, Keycode::P),
+    (0x1A, Keycode::LeftBracket),
+    (0x1B, Keycode::RightBracket),
+    (0x1C, Keycode::Enter),
+    (0x1D, Keycode::LControl),
+    (0x1E, Keycode::A),
+    (0x1F, Keycode::S),
+    (0x20, Keycode::D),
+    (0x21, Keycode::F),
+    (0x22, Keycode::G),
+    (0x23, Keycode::H),
+    (0x24, Keycode::J),
+    (0x25, Keycode::K),
+    (0x26, Keycode::L),
+    (0x27, Keycode::Semicolon),
+    (0x28, Keycode::Apostrophe),
+    (0x29, Keycode::Grave),
+    (0x2A, Keycode::LShift),
+    (0x2B, Keycode::BackSlash),
+    (0x2C, Keycode::Z),
+    (0x2D, Keycode::X),
+    (0x2E, Keycode::C),
+    (0x2F, Keycode::V),
+    (0x30, Keycode::B),
+    (0x31, Keycode::N),
+    (0x32, Keycode::M),
+    (0x33, Keycode::Comma),
+    (0x34, Keycode::Dot),
+    (0x35, Keycode::Slash),
+    (0x36, Keycode::RShift),
+    (0x37, Keycode::NumpadMultiply),
+    (0x38, Keycode::LAlt),
+    (0x39, Keycode::Space),
+    (0x3A, Keycode::CapsLock),
+    (0x3B, Keycode::F1),
+    (0x3C, Keycode::F2),
+    (0x3D, Keycode::F3),
+    (0x3E, Keycode::F4),
+    (0x3F, Keycode::F5),
+    (0x40, Keycode::F6),
+    (0x41, Keycode::F7),
+    (0x42, Keycode::F8),
+    (0x43, Keycode::F9),
+    (0x44, Keycode::F10),
+    (0x45, Keycode::NumLock),
+    (0x46, Keycode::ScrollLock),
+    (0x47, Keycode::Numpad7),
+    (0x48, Keycode::Numpad8),
+    (0x49, Keycode::Numpad9),
+    (0x4A, Keycode::NumpadSubtract),
+    (0x4B, Keycode::Numpad4),
+    (0x4C, Keycode::Numpad5),
+    (0x4D, Keycode::Numpad6),
+    (0x4E, Keycode::NumpadAdd),
+    (0x4F, Keycode::Numpad1),
+    (0x50, Keycode::Numpad2),
+    (0x51, Keycode::Numpad3),
+    (0x52, Keycode::Numpad0),
+    (0x53, Keycode::NumpadDecimal),
+    (0x56, Keycode::Oem102),
+    (0x57, Keycode::F11),
+    (0x58, Keycode::F12),
+    (0x64, Keycode::F13),
+    (0x65, Keycode::F14),
+    (0x66, Keycode::F15),
+    (0x67, Keycode::F16),
+    (0x68, Keycode::F17),
+    (0x69, Keycode::F18),
+    (0x6A, Keycode::F19),
+    (0x6B, Keycode::F20),
+    (0x6C, Keycode::F21),
+    (0x6D, Keycode::F22),
+    (0x6E, Keycode::F23),
+    (0x76, Keycode::F24),
+    (0xE01C, Keycode::NumpadEnter),
+    (0xE01D, Keycode::RControl),
+    (0xE035, Keycode::NumpadDivide),
+    (0xE037, Keycode::PrintScreen),
+    (0xE038, Keycode::RAlt),
+    (0xE045, Keycode::NumLock),
+    (0xE047, Keycode::Home),
+    (0xE048, Keycode::Up),
+    (0xE049, Keycode::PageUp),
+    (0xE04B, Keycode::Left),
+    (0xE04D, Keycode::Right),
+    (0xE04F, Keycode::End),
+    (0xE050, Keycode::Down),
+    (0xE051, Keycode::PageDown),
+    (0xE052, Keycode::Insert),
+    (0xE053, Keycode::Delete),
+    (0xE05B, Keycode::LMeta),
+    (0xE05C, Keycode::RMeta),
+    (0xE05D, Keycode::Apps),
+];
+
+// The key at a US keyboard position, None for positions without a name
+pub fn map_scan_code(code: u16) -> Option<Keycode> {
+    SCAN_CODES.iter()
+        .find(|(scan_code, _)| *scan_code == code)
+        .map(|(_, keycode)| *keycode)
+}
+
+// Everything a key going down is reported as: its name, by position when `physical_keys` is on, and
+// its scan code for SC_ binds. Keys without a US position, like media keys, keep their virtual-key name
+pub fn reported_keycodes(virtual_key: u8, scan_code: Option<u16>, physical_keys: bool) -> Vec<Keycode> {
+    let Some(keycode) = map_virtual_key(virtual_key) else {
+        return Vec::new();
+    };
+    let position = scan_code.filter(|_| physical_keys).and_then(map_scan_code);
+    let mut keycodes = vec![position.unwrap_or(keycode)];
+    keycodes.extend(scan_code.map(Keycode::ScanCode));
+    keycodes
+}
+
+pub fn scan_code(keycode: Keycode) -> Option<u16> {
+    match keycode {
+        Keycode::ScanCode(code) => Some(code),
+        _ => SCAN_CODES.iter().find(|(_, named)| *named == keycode).map(|(code, _)| *code),
+    }
+}
+
+pub fn virtual_key_code(keycode: Keycode) -> Option<u8> {
+    match keycode {
+        Keycode::VirtualKey(code) => Some(code),
+        _ => VIRTUAL_KEYS.iter().find(|(_, named)| *named == keycode).map(|(code, _)| *code),
+    }
+}
+
+pub fn map_modifier(modifier: &Modifier) -> (Keycode, Option<Keycode>) {
+    match modifier {
+        Modifier::Alt => { (Keycode::LAlt, Some(Keycode::RAlt)) }
+        Modifier::Control => { (Keycode::LControl, Some(Keycode::RControl)) }
+        Modifier::Shift => { (Keycode::LShift, Some(Keycode::RShift)) }
+        Modifier::Meta => { (Keycode::LMeta, Some(Keycode::RMeta)) }
+        Modifier::LeftAlt => { (Keycode::LAlt, None) }
+        Modifier::RightAlt => { (Keycode::RAlt, None) }
+        Modifier::LeftControl => { (Keycode::LControl, None) }
+        Modifier::RightControl => { (Keycode::RControl, None) }
+        Modifier::LeftShift => { (Keycode::LShift, None) }
+        Modifier::RightShift => { (Keycode::RShift, None) }
+        Modifier::LeftMeta => { (Keycode::LMeta, None) }
+        Modifier::RightMeta => { (Keycode::RMeta, None) }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,123 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    time::{Duration, Instant},
+    vec::IntoIter,
+};
+
+use crate::{
+    input::{InputFrame, InputSource},
+    keycode::Keycode,
+};
+
+// One line per frame: the microseconds since the recording started, then the pressed keys joined
+// with '+', or '-' when nothing is pressed. e.g. "1516032 LShift+Key1"
+fn format_frame(elapsed: Duration, pressed_keys: &[Keycode]) -> String {
+    let keys = pressed_keys.iter().map(Keycode::as_str).collect::<Vec<_>>().join("+");
+    format!("{} {}", elapsed.as_micros(), if keys.is_empty() { "-" } else { &keys })
+}
+
+fn parse_frame(line: &str) -> Result<(Duration, Vec<Keycode>), String> {
+    let (elapsed, keys) = line.split_once(' ').ok_or("expected '<microseconds> <keys>'")?;
+    let elapsed = elapsed.parse::<u64>()
+        .map_err(|e| format!("invalid timestamp '{}': {}", elapsed, e))?;
+
+    let pressed_keys = match keys.trim() {
+        "-" => Vec::new(),
+        keys => keys.split('+').map(str::parse).collect::<Result<Vec<Keycode>, String>>()?,
+    };
+
+    Ok((Duration::from_micros(elapsed), pressed_keys))
+}
+
+// Passes frames through from another source while appending each of them to a file
+pub struct RecordingInputSource<S> {
+    source: S,
+    writer: Option<BufWriter<File>>,
+    started: Option<Instant>,
+}
+
+impl<S: InputSource> RecordingInputSource<S> {
+    pub fn create(source: S, path: &Path) -> io::Result<Self> {
+        Ok(RecordingInputSource::new(source, File::create(path)?))
+    }
+
+    // For sources that can only be started once, so the file has to be opened before the source is given up
+    pub fn new(source: S, file: File) -> Self {
+        RecordingInputSource {
+            source,
+            writer: Some(BufWriter::new(file)),
+            started: None,
+        }
+    }
+
+    fn record(&mut self, frame: &InputFrame) {
+        let started = *self.started.get_or_insert(frame.timestamp);
+
+        if let Some(writer) = &mut self.writer {
+            let line = format_frame(frame.timestamp.duration_since(started), &frame.pressed_keys);
+            // A failing recording should never take input down with it, so just stop recording
+            if writeln!(writer, "{}", line).and_then(|_| writer.flush()).is_err() {
+                self.writer = None;
+            }
+        }
+    }
+}
+
+impl<S: InputSource> InputSource for RecordingInputSource<S> {
+    fn poll(&mut self) -> Option<InputFrame> {
+        let frame = self.source.poll()?;
+        self.record(&frame);
+        Some(frame)
+    }
+
+    // Each change gets its own line, so a replay sees the short taps too
+    fn poll_changes(&mut self) -> Vec<InputFrame> {
+        let frames = self.source.poll_changes();
+        for frame in &frames {
+            self.record(frame);
+        }
+        frames
+    }
+}
+
+// Plays back a file written by RecordingInputSource, with timestamps relative to when the replay
+// was opened
+pub struct ReplayInputSource {
+    frames: IntoIter<(Duration, Vec<Keycode>)>,
+    started: Instant,
+}
+
+impl ReplayInputSource {
+    pub fn open(path: &Path, started: Instant) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut frames = Vec::new();
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let frame = parse_frame(&line).map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("{}:{}: {}", path.display(), index + 1, e))
+            })?;
+            frames.push(frame);
+        }
+
+        Ok(ReplayInputSource {
+            frames: frames.into_iter(),
+            started,
+        })
+    }
+}
+
+impl InputSource for ReplayInputSource {
+    fn poll(&mut self) -> Option<InputFrame> {
+        let (elapsed, pressed_keys) = self.frames.next()?;
+        Some(InputFrame {
+            timestamp: self.started + elapsed,
+            pressed_keys,
+        })
+    }
+}
@@ -0,0 +1,182 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::config::{load_config, Keybind, Layer, LoadedConfig, OtherSettings, GROUP_KEY_PREFIX, LAYER_SECTION_PREFIX};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+// Polls the config's modified time so it can be checked from the game thread without a watcher
+// thread. Checks are rate limited, so calling poll every frame is cheap
+pub struct ConfigWatcher {
+    path: PathBuf,
+    last_modified: Option<SystemTime>,
+    last_checked: Instant,
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl ConfigWatcher {
+    pub fn new(path: &Path, now: Instant) -> Self {
+        ConfigWatcher {
+            path: path.to_path_buf(),
+            last_modified: modified_time(path),
+            last_checked: now,
+        }
+    }
+
+    // Returns the freshly loaded config when the file changed since the last check
+    pub fn poll(&mut self, now: Instant) -> Option<LoadedConfig> {
+        if now.duration_since(self.last_checked) < CHECK_INTERVAL {
+            return None;
+        }
+        self.last_checked = now;
+
+        // A missing file is most likely an editor in the middle of saving, keep the current config
+        let modified = modified_time(&self.path)?;
+        if self.last_modified == Some(modified) {
+            return None;
+        }
+        self.last_modified = Some(modified);
+
+        Some(load_config(&self.path))
+    }
+}
+
+fn shortcuts_by_action(keybinds: &[Keybind]) -> BTreeMap<String, Vec<String>> {
+    let mut by_action: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for keybind in keybinds {
+        let action = match &keybind.layer {
+            Some(layer) => format!("{}{}.{}", LAYER_SECTION_PREFIX, layer, keybind.action),
+            None => keybind.action.to_string(),
+        };
+        by_action.entry(action).or_default().push(keybind.format_bind());
+    }
+    for shortcuts in by_action.values_mut() {
+        shortcuts.sort();
+    }
+    by_action
+}
+
+// "hold:CapsLock", as written after activate =
+fn format_activation(layer: &Layer) -> String {
+    format!("{}:{}", layer.activation, layer.shortcut)
+}
+
+// One human readable line per keybind, layer or setting that differs, for the log after a reload
+pub fn describe_config_changes(
+    old_keybinds: &[Keybind],
+    old_layers: &[Layer],
+    old_settings: &OtherSettings,
+    new_keybinds: &[Keybind],
+    new_layers: &[Layer],
+    new_settings: &OtherSettings,
+) -> Vec<String> {
+    let old_by_action = shortcuts_by_action(old_keybinds);
+    let new_by_action = shortcuts_by_action(new_keybinds);
+    let mut changes = Vec::new();
+
+    for (action, old_shortcuts) in &old_by_action {
+        match new_by_action.get(action) {
+            None => changes.push(format!("{}: {} -> unbound", action, old_shortcuts.join(", "))),
+            Some(new_shortcuts) if new_shortcuts != old_shortcuts => {
+                changes.push(format!("{}: {} -> {}", action, old_shortcuts.join(", "), new_shortcuts.join(", ")));
+            }
+            Some(_) => {}
+        }
+    }
+    for (action, new_shortcuts) in &new_by_action {
+        if !old_by_action.contains_key(action) {
+            changes.push(format!("{}: unbound -> {}", action, new_shortcuts.join(", ")));
+        }
+    }
+    for layer in old_layers {
+        match new_layers.iter().find(|l| l.name == layer.name) {
+            None => changes.push(format!("{}{}.activate: {} -> unset", LAYER_SECTION_PREFIX, layer.name, format_activation(layer))),
+            Some(new_layer) if format_activation(new_layer) != format_activation(layer) => changes.push(format!(
+                "{}{}.activate: {} -> {}",
+                LAYER_SECTION_PREFIX, layer.name, format_activation(layer), format_activation(new_layer)
+            )),
+            Some(_) => {}
+        }
+    }
+    for layer in new_layers {
+        if !old_layers.iter().any(|l| l.name == layer.name) {
+            changes.push(format!("{}{}.activate: unset -> {}", LAYER_SECTION_PREFIX, layer.name, format_activation(layer)));
+        }
+    }
+
+    if old_settings.cycle_debouce_milliseconds != new_settings.cycle_debouce_milliseconds {
+        changes.push(format!(
+            "cycle_debounce_milliseconds: {} -> {}",
+            old_settings.cycle_debouce_milliseconds, new_settings.cycle_debouce_milliseconds
+        ));
+    }
+    if old_settings.sequence_timeout_milliseconds != new_settings.sequence_timeout_milliseconds {
+        changes.push(format!(
+            "sequence_timeout_milliseconds: {} -> {}",
+            old_settings.sequence_timeout_milliseconds, new_settings.sequence_timeout_milliseconds
+        ));
+    }
+    if old_settings.exact_modifiers != new_settings.exact_modifiers {
+        changes.push(format!("exact_modifiers: {} -> {}", old_settings.exact_modifiers, new_settings.exact_modifiers));
+    }
+    if old_settings.modifier_keys != new_settings.modifier_keys {
+        changes.push(format!("modifier_keys: {} -> {}", old_settings.modifier_keys, new_settings.modifier_keys));
+    }
+    if old_settings.cycle_forward_at_end != new_settings.cycle_forward_at_end {
+        changes.push(format!(
+            "cycle_forward_at_end: {} -> {}",
+            old_settings.cycle_forward_at_end, new_settings.cycle_forward_at_end
+        ));
+    }
+    if old_settings.cycle_back_at_start != new_settings.cycle_back_at_start {
+        changes.push(format!(
+            "cycle_back_at_start: {} -> {}",
+            old_settings.cycle_back_at_start, new_settings.cycle_back_at_start
+        ));
+    }
+    if old_settings.memory_slot_past_end != new_settings.memory_slot_past_end {
+        changes.push(format!(
+            "memory_slot_past_end: {} -> {}",
+            old_settings.memory_slot_past_end, new_settings.memory_slot_past_end
+        ));
+    }
+    if old_settings.ignore_empty_memory_slots != new_settings.ignore_empty_memory_slots {
+        changes.push(format!(
+            "ignore_empty_memory_slots: {} -> {}",
+            old_settings.ignore_empty_memory_slots, new_settings.ignore_empty_memory_slots
+        ));
+    }
+    for group in &old_settings.groups {
+        match new_settings.groups.iter().find(|g| g.name == group.name) {
+            None => changes.push(format!("{}{}: {} -> unset", GROUP_KEY_PREFIX, group.name, group.format_slots())),
+            Some(new_group) if new_group.slots != group.slots => changes.push(format!(
+                "{}{}: {} -> {}",
+                GROUP_KEY_PREFIX, group.name, group.format_slots(), new_group.format_slots()
+            )),
+            Some(_) => {}
+        }
+    }
+    for group in &new_settings.groups {
+        if !old_settings.groups.iter().any(|g| g.name == group.name) {
+            changes.push(format!("{}{}: unset -> {}", GROUP_KEY_PREFIX, group.name, group.format_slots()));
+        }
+    }
+    if old_settings.physical_keys != new_settings.physical_keys {
+        changes.push(format!("physical_keys: {} -> {}", old_settings.physical_keys, new_settings.physical_keys));
+    }
+    if old_settings.record_input != new_settings.record_input {
+        changes.push(format!(
+            "record_input: {} -> {} (takes effect after restarting the game)",
+            old_settings.record_input, new_settings.record_input
+        ));
+    }
+
+    changes
+}
@@ -0,0 +1,398 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    bindings::{is_keybinding_matched, Keybinding, LayerBinding},
+    config::{Action, ModifierKeys, OtherSettings, SlotBoundary, Trigger, DEFAULT_TAP_MILLISECONDS},
+    game::GameState,
+    keycode::Keycode,
+    layers::LayerStack,
+    sequence::SequenceMatcher,
+    slots::{
+        back_cycle_group, back_cycle_memory_slot, forward_cycle_group, forward_cycle_memory_slot, select_filled_slot,
+        select_spell, set_memory_slot, MagicSlots,
+    },
+    spells::{slot_label, spell_label},
+};
+
+const HUD_UPDATE_REBOUND: Duration = Duration::from_secs(3);
+
+// A matched key set together with the layer whose binds it was found in, None for [keybinds]
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct BoundKeys {
+    layer: Option<String>,
+    keys: HashSet<Keycode>,
+}
+
+// The key set currently down, for resolving tap and hold binds
+struct TimedPress {
+    keys: BoundKeys,
+    pressed_at: Instant,
+    // Set once a hold or double tap fired, so the release doesn't also count as a tap
+    is_resolved: bool,
+}
+
+// A tap on keys that also have a double tap bind, waiting to see whether a second press follows
+struct PendingTap {
+    keys: BoundKeys,
+    action: Option<Action>,
+    released_at: Instant,
+    window: Duration,
+}
+
+// The [settings] that decide what slot actions do at the ends of the list and on empty slots
+struct SlotSettings {
+    cycle_forward_at_end: SlotBoundary,
+    cycle_back_at_start: SlotBoundary,
+    memory_slot_past_end: SlotBoundary,
+    ignore_empty_memory_slots: bool,
+    // Each group.<name> setting's slots as indices
+    groups: HashMap<String, Vec<usize>>,
+}
+
+impl SlotSettings {
+    fn group(&self, name: &str) -> &[usize] {
+        self.groups.get(name).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+impl From<&OtherSettings> for SlotSettings {
+    fn from(other_settings: &OtherSettings) -> Self {
+        SlotSettings {
+            cycle_forward_at_end: other_settings.cycle_forward_at_end,
+            cycle_back_at_start: other_settings.cycle_back_at_start,
+            memory_slot_past_end: other_settings.memory_slot_past_end,
+            ignore_empty_memory_slots: other_settings.ignore_empty_memory_slots,
+            groups: other_settings.groups.iter()
+                .map(|group| (group.name.clone(), group.slots.iter().map(|slot| usize::from(*slot) - 1).collect()))
+                .collect(),
+        }
+    }
+}
+
+pub struct Remapper<H> {
+    keybindings: Vec<Keybinding>,
+    cycle_rebound: Duration,
+    exact_modifiers: bool,
+    modifier_keys: ModifierKeys,
+    slot_settings: SlotSettings,
+    // The selected slot as of the last look, and the one before it changed, for swap_to_previous
+    current_slot: Option<i32>,
+    previous_slot: Option<i32>,
+    // The slot each group last selected, where its next cycle carries on from
+    group_cursors: HashMap<String, usize>,
+    last_cycle_back_run: Instant,
+    last_cycle_forward_run: Instant,
+    last_hud_update_run: Instant,
+    player_hud_type: Option<H>,
+    is_hud_restored: bool,
+    // The keys of the binding matched last frame, diffed against this frame's match for press and release
+    held_keys: Option<BoundKeys>,
+    timed_press: Option<TimedPress>,
+    pending_tap: Option<PendingTap>,
+    sequences: SequenceMatcher,
+    layers: LayerStack,
+    // Things worth telling the player about, picked up by whoever owns the log
+    messages: Vec<String>,
+}
+
+impl<H: Copy + PartialEq> Remapper<H> {
+    pub fn new(keybindings: Vec<Keybinding>, layers: Vec<LayerBinding>, other_settings: &OtherSettings, now: Instant) -> Self {
+        Remapper {
+            keybindings,
+            cycle_rebound: Duration::from_millis(other_settings.cycle_debouce_milliseconds),
+            exact_modifiers: other_settings.exact_modifiers,
+            modifier_keys: other_settings.modifier_keys.clone(),
+            slot_settings: SlotSettings::from(other_settings),
+            current_slot: None,
+            previous_slot: None,
+            group_cursors: HashMap::new(),
+            last_cycle_back_run: now,
+            last_cycle_forward_run: now,
+            last_hud_update_run: now,
+            player_hud_type: None,
+            is_hud_restored: true,
+            held_keys: None,
+            timed_press: None,
+            pending_tap: None,
+            sequences: SequenceMatcher::new(Duration::from_millis(other_settings.sequence_timeout_milliseconds), now),
+            layers: LayerStack::new(layers),
+            messages: Vec::new(),
+        }
+    }
+
+    // Swaps in a reloaded config between two frames. Debounce and HUD timers carry over
+    pub fn reload(&mut self, keybindings: Vec<Keybinding>, layers: Vec<LayerBinding>, other_settings: &OtherSettings) {
+        self.keybindings = keybindings;
+        self.layers.reload(layers);
+        self.cycle_rebound = Duration::from_millis(other_settings.cycle_debouce_milliseconds);
+        self.exact_modifiers = other_settings.exact_modifiers;
+        self.modifier_keys = other_settings.modifier_keys.clone();
+        self.slot_settings = SlotSettings::from(other_settings);
+        self.sequences.reset(Duration::from_millis(other_settings.sequence_timeout_milliseconds));
+    }
+
+    pub fn keybindings(&self) -> &[Keybinding] {
+        &self.keybindings
+    }
+
+    // The messages left by the frames run since the last call
+    pub fn take_messages(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.messages)
+    }
+
+    // Runs once per game frame. `now` is passed in rather than read from the clock so the
+    // simulator can drive the loop with scripted timestamps
+    pub fn run_frame<G: GameState<HudType = H>>(&mut self, game: &mut G, pressed_keys: &[Keycode], now: Instant) {
+        if game.player_hp() <= 0 {
+            return;
+        }
+
+        if self.player_hud_type.is_none() {
+            self.player_hud_type = Some(game.hud_type());
+        }
+
+        let is_need_to_restore_hud = !self.is_hud_restored &&
+            now.duration_since(self.last_hud_update_run) > HUD_UPDATE_REBOUND &&
+            game.hud_type() != self.player_hud_type.unwrap();
+
+        if is_need_to_restore_hud {
+            game.set_hud_type(self.player_hud_type.unwrap());
+            self.is_hud_restored = true;
+        }
+
+        // Picks up the game's own D-pad cycling since the last frame
+        self.track_slot(game);
+
+        self.layers.update(pressed_keys);
+        let active_layers = self.layers.active_layers();
+        let is_available = |k: &Keybinding| k.layer.as_ref().is_none_or(|layer| active_layers.contains(layer));
+
+        let mut actions = Vec::new();
+        actions.extend(self.sequences.advance(&self.keybindings, is_available, pressed_keys, now));
+
+        // Active layers are looked at first, most recent on top, then [keybinds]. Within a table the most
+        // specific fully pressed key set wins, and every trigger bound to exactly those keys gets a look.
+        // Keys pressed as a step of a sequence don't also fire the plain bind on the same keys
+        let consumed_keys = self.sequences.consumed_keys(pressed_keys);
+        let matched_keys = active_layers.iter()
+            .map(Some)
+            .chain(std::iter::once(None))
+            .find_map(|layer| {
+                self.keybindings.iter().find(|k| {
+                    let is_exact = k.exact_modifiers.unwrap_or(self.exact_modifiers);
+                    k.layer.as_ref() == layer && k.leader.is_empty() &&
+                        is_keybinding_matched(k, pressed_keys, is_exact, &self.modifier_keys)
+                })
+            })
+            .filter(|k| consumed_keys != Some(&k.keys))
+            .map(|k| BoundKeys { layer: k.layer.clone(), keys: k.keys.clone() });
+        let previous_keys = std::mem::replace(&mut self.held_keys, matched_keys.clone());
+        let is_changed = matched_keys != previous_keys;
+
+        // A waiting tap fires once its double tap window passes or something else gets pressed
+        if let Some(pending) = &self.pending_tap {
+            let is_expired = now.duration_since(pending.released_at) > pending.window;
+            let is_other_press = is_changed && matched_keys.as_ref().is_some_and(|keys| *keys != pending.keys);
+            if is_expired || is_other_press {
+                actions.extend(pending.action.clone());
+                self.pending_tap = None;
+            }
+        }
+
+        if is_changed {
+            if let Some(keys) = &previous_keys {
+                actions.extend(self.bound_action(keys, |t| *t == Trigger::OnRelease));
+                actions.extend(self.release_timed(keys, now));
+            }
+            if let Some(keys) = &matched_keys {
+                actions.extend(self.bound_action(keys, |t| *t == Trigger::OnPress));
+                actions.extend(self.press_timed(keys, now));
+            }
+        }
+        if let Some(keys) = &matched_keys {
+            actions.extend(self.bound_action(keys, |t| *t == Trigger::WhileHeld));
+            actions.extend(self.hold_timed(now));
+        }
+
+        if is_changed && matched_keys.is_some() {
+            self.layers.release_one_shot();
+        }
+
+        for action in actions {
+            self.run_action(game, action, now);
+            self.track_slot(game);
+        }
+
+        let is_cycling = matched_keys.as_ref().is_some_and(|keys| {
+            self.keybindings.iter().any(|k| {
+                k.leader.is_empty() && k.layer == keys.layer && k.keys == keys.keys &&
+                    matches!(
+                        k.action,
+                        Action::CycleBack | Action::CycleForward | Action::CycleGroupBack(_) | Action::CycleGroupForward(_)
+                    )
+            })
+        });
+        if !is_cycling {
+            // When no keys are pressed, it's safe to reset the debounce so the player can press the buttons rapidly
+            self.last_cycle_forward_run = now - self.cycle_rebound;
+            self.last_cycle_back_run = now - self.cycle_rebound;
+        }
+    }
+
+    // Binds are sorted by precedence, so the first bind for a trigger is the one that wins
+    fn bound(&self, keys: &BoundKeys, is_trigger: impl Fn(&Trigger) -> bool) -> Option<&Keybinding> {
+        self.keybindings.iter()
+            .find(|k| k.leader.is_empty() && k.layer == keys.layer && k.keys == keys.keys && is_trigger(&k.trigger))
+    }
+
+    fn bound_action(&self, keys: &BoundKeys, is_trigger: impl Fn(&Trigger) -> bool) -> Option<Action> {
+        self.bound(keys, is_trigger).map(|k| k.action.clone())
+    }
+
+    // A second press inside the double tap window fires the double tap bind straight away
+    fn press_timed(&mut self, keys: &BoundKeys, now: Instant) -> Option<Action> {
+        let is_double_tap = self.pending_tap.as_ref().is_some_and(|pending| pending.keys == *keys);
+        self.timed_press = Some(TimedPress {
+            keys: keys.clone(),
+            pressed_at: now,
+            is_resolved: is_double_tap,
+        });
+        if !is_double_tap {
+            return None;
+        }
+        self.pending_tap = None;
+        self.bound_action(keys, |t| matches!(t, Trigger::DoubleTap(_)))
+    }
+
+    fn hold_timed(&mut self, now: Instant) -> Option<Action> {
+        let press = self.timed_press.as_ref().filter(|press| !press.is_resolved)?;
+        let held_for = now.duration_since(press.pressed_at);
+        let action = self.bound(&press.keys, |t| matches!(t, Trigger::Hold(duration) if held_for >= *duration))?.action.clone();
+        self.timed_press.as_mut()?.is_resolved = true;
+        Some(action)
+    }
+
+    // A short enough press is a tap. It fires on release, or after the double tap window when the
+    // same keys also have a double tap bind
+    fn release_timed(&mut self, keys: &BoundKeys, now: Instant) -> Option<Action> {
+        let press = self.timed_press.take().filter(|press| press.keys == *keys && !press.is_resolved)?;
+        let held_for = now.duration_since(press.pressed_at);
+
+        let tap = self.bound(keys, |t| matches!(t, Trigger::Tap(_)));
+        let tap_limit = match tap.map(|k| k.trigger) {
+            Some(Trigger::Tap(limit)) => limit,
+            _ => Duration::from_millis(DEFAULT_TAP_MILLISECONDS),
+        };
+        if held_for > tap_limit {
+            return None;
+        }
+        let tap_action = tap.map(|k| k.action.clone());
+
+        match self.bound(keys, |t| matches!(t, Trigger::DoubleTap(_))).map(|k| k.trigger) {
+            Some(Trigger::DoubleTap(window)) => {
+                self.pending_tap = Some(PendingTap {
+                    keys: keys.clone(),
+                    action: tap_action,
+                    released_at: now,
+                    window,
+                });
+                None
+            }
+            _ => tap_action,
+        }
+    }
+
+    fn track_slot(&mut self, game: &impl MagicSlots) {
+        let slot = game.selected_slot();
+        if self.current_slot.is_some_and(|current| current != slot) {
+            self.previous_slot = self.current_slot;
+        }
+        self.current_slot = Some(slot);
+    }
+
+    // Shows the HUD so the player sees the new slot, it goes back to their setting after HUD_UPDATE_REBOUND
+    fn show_hud<G: GameState<HudType = H>>(&mut self, game: &mut G, now: Instant) {
+        game.set_hud_type(G::HUD_ON);
+        self.last_hud_update_run = now;
+        self.is_hud_restored = false;
+    }
+
+    fn run_action<G: GameState<HudType = H>>(&mut self, game: &mut G, action: Action, now: Instant) {
+        let previous_slot = game.selected_slot();
+        match action {
+            Action::SetMemorySlot(slot) => {
+                let settings = &self.slot_settings;
+                if set_memory_slot(game, slot - 1, settings.memory_slot_past_end, settings.ignore_empty_memory_slots) {
+                    self.show_hud(game, now);
+                }
+            }
+            Action::CycleBack => {
+                if now.duration_since(self.last_cycle_back_run) < self.cycle_rebound {
+                    return;
+                }
+                if back_cycle_memory_slot(game, self.slot_settings.cycle_back_at_start) {
+                    self.show_hud(game, now);
+                }
+                self.last_cycle_back_run = now;
+            }
+            Action::CycleForward => {
+                if now.duration_since(self.last_cycle_forward_run) < self.cycle_rebound {
+                    return;
+                }
+                if forward_cycle_memory_slot(game, self.slot_settings.cycle_forward_at_end) {
+                    self.show_hud(game, now);
+                }
+                self.last_cycle_forward_run = now;
+            }
+            // Groups share the debounce of the plain cycle binds going the same way
+            Action::CycleGroupBack(group) => {
+                if now.duration_since(self.last_cycle_back_run) < self.cycle_rebound {
+                    return;
+                }
+                let cursor = self.group_cursors.get(&group).copied();
+                let at_start = self.slot_settings.cycle_back_at_start;
+                if back_cycle_group(game, self.slot_settings.group(&group), cursor, at_start) {
+                    self.group_cursors.insert(group, game.selected_slot() as usize);
+                    self.show_hud(game, now);
+                }
+                self.last_cycle_back_run = now;
+            }
+            Action::CycleGroupForward(group) => {
+                if now.duration_since(self.last_cycle_forward_run) < self.cycle_rebound {
+                    return;
+                }
+                let cursor = self.group_cursors.get(&group).copied();
+                let at_end = self.slot_settings.cycle_forward_at_end;
+                if forward_cycle_group(game, self.slot_settings.group(&group), cursor, at_end) {
+                    self.group_cursors.insert(group, game.selected_slot() as usize);
+                    self.show_hud(game, now);
+                }
+                self.last_cycle_forward_run = now;
+            }
+            Action::SwapToPrevious => {
+                let Some(slot) = self.previous_slot.and_then(|slot| usize::try_from(slot).ok()) else {
+                    return;
+                };
+                if select_filled_slot(game, slot) {
+                    self.show_hud(game, now);
+                }
+            }
+            Action::SelectSpell(param_id) => {
+                if select_spell(game, param_id).is_none() {
+                    self.messages.push(format!("{} is not attuned", spell_label(param_id)));
+                    return;
+                }
+                self.show_hud(game, now);
+            }
+            Action::NoOp => { }
+        }
+
+        let slot = game.selected_slot();
+        if slot != previous_slot {
+            self.messages.push(format!("selected {}", slot_label(&game.param_ids(), slot)));
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    diagnostics::{Diagnostic, DiagnosticKind},
+    ini_document::IniDocument,
+};
+
+// Bump this whenever a section or key gets renamed, and add the old name to LEGACY_SECTIONS
+pub const CONFIG_VERSION: u32 = 2;
+
+// Files without a config_version key were written before versioning existed
+const UNVERSIONED_CONFIG_VERSION: u32 = 1;
+
+// (legacy section name, current section name)
+const LEGACY_SECTIONS: &[(&str, &str)] = &[
+    ("other_settings", "settings"),
+];
+
+// Moves legacy sections to their current names and returns the version the file declared
+pub fn migrate_document(path: &Path, document: &mut IniDocument, diagnostics: &mut Vec<Diagnostic>) -> u32 {
+    for (legacy_name, current_name) in LEGACY_SECTIONS {
+        if document.section(legacy_name).is_none() {
+            continue;
+        }
+        diagnostics.push(Diagnostic::new(
+            DiagnosticKind::DeprecatedSection, path, document.section_line(legacy_name), None,
+            format!("[{}] is deprecated, reading it as [{}]", legacy_name, current_name),
+        ).with_suggestion(format!("rename the section to [{}]", current_name)));
+        document.rename_section(legacy_name, current_name);
+    }
+
+    let Some(entry) = document.get("settings", "config_version") else {
+        return UNVERSIONED_CONFIG_VERSION;
+    };
+    match entry.value.parse::<u32>() {
+        Ok(version) if version > CONFIG_VERSION => {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::UnsupportedVersion, path, Some(entry.line), Some(&entry.key),
+                format!("version {} is newer than this remapper understands ({}), some entries may be ignored", version, CONFIG_VERSION),
+            ).with_suggestion("update the remapper dll"));
+            version
+        }
+        Ok(version) => version,
+        Err(_) => {
+            diagnostics.push(Diagnostic::new(
+                DiagnosticKind::InvalidValue, path, Some(entry.line), Some(&entry.key),
+                format!("'{}' is not a config version, treating the file as unversioned", entry.value),
+            ));
+            UNVERSIONED_CONFIG_VERSION
+        }
+    }
+}
+
+fn section_header_name(line: &str) -> Option<String> {
+    let name = line.trim().strip_prefix('[')?.strip_suffix(']')?;
+    Some(name.trim().to_lowercase())
+}
+
+// Renames legacy section headers. A legacy section whose current name is already in the file has its
+// entries moved to the top of that section instead, so the entries written under the current name still win
+fn migrate_section_lines(text: &str) -> Vec<String> {
+    // The lines before the first header, then one block per section starting with its header
+    let mut blocks = vec![Vec::new()];
+    for line in text.lines() {
+        if section_header_name(line).is_some() {
+            blocks.push(Vec::new());
+        }
+        blocks.last_mut().unwrap().push(line.to_string());
+    }
+    let block_name = |block: &[String]| block.first().and_then(|line| section_header_name(line));
+
+    for (legacy_name, current_name) in LEGACY_SECTIONS {
+        let mut target = blocks.iter().position(|block| block_name(block).as_deref() == Some(*current_name));
+        let mut index = 0;
+        while index < blocks.len() {
+            if block_name(&blocks[index]).as_deref() != Some(*legacy_name) {
+                index += 1;
+                continue;
+            }
+            let Some(target_index) = target else {
+                blocks[index][0] = format!("[{}]", current_name);
+                target = Some(index);
+                index += 1;
+                continue;
+            };
+
+            let mut entries = blocks.remove(index).split_off(1);
+            while entries.last().is_some_and(|line| line.trim().is_empty()) {
+                entries.pop();
+            }
+            let target_index = if target_index > index { target_index - 1 } else { target_index };
+            blocks[target_index].splice(1..1, entries);
+            target = Some(target_index);
+        }
+    }
+    blocks.concat()
+}
+
+// Rewrites an older config to the current layout. Comments, ordering and values are kept, only the
+// section headers and config_version line change. A file from a newer remapper is returned unchanged
+pub fn migrate_config_text(path: &Path, text: &str, diagnostics: &mut Vec<Diagnostic>) -> String {
+    let mut document = IniDocument::parse(path, text, &mut Vec::new());
+    let version = migrate_document(path, &mut document, &mut Vec::new());
+    if version > CONFIG_VERSION {
+        let entry = document.get("settings", "config_version");
+        diagnostics.push(Diagnostic::new(
+            DiagnosticKind::UnsupportedVersion, path, entry.map(|e| e.line), entry.map(|e| e.key.as_str()),
+            format!("version {} is newer than this remapper understands ({}), leaving the file as it is", version, CONFIG_VERSION),
+        ).with_suggestion("update the remapper"));
+        return text.to_string();
+    }
+
+    let mut lines = migrate_section_lines(text);
+
+    let version_line = format!("config_version = {}", CONFIG_VERSION);
+    let existing_version = lines.iter().position(|line| {
+        line.split_once('=').is_some_and(|(key, _)| key.trim().eq_ignore_ascii_case("config_version"))
+    });
+    let settings_header = lines.iter().position(|line| section_header_name(line).as_deref() == Some("settings"));
+
+    match (existing_version, settings_header) {
+        (Some(index), _) => lines[index] = version_line,
+        (None, Some(index)) => lines.insert(index + 1, version_line),
+        (None, None) => {
+            lines.push(String::new());
+            lines.push("[settings]".to_string());
+            lines.push(version_line);
+        }
+    }
+
+    let mut migrated = lines.join("\n");
+    if text.ends_with('\n') {
+        migrated.push('\n');
+    }
+    migrated
+}
+
+// Migrates the file in place after copying the original next to it. Returns the backup's path, or None
+// when the file is left alone because it is newer than CONFIG_VERSION
+pub fn rewrite_config_file(path: &Path, diagnostics: &mut Vec<Diagnostic>) -> io::Result<Option<PathBuf>> {
+    let text = fs::read_to_string(path)?;
+    let reported = diagnostics.len();
+    let migrated = migrate_config_text(path, &text, diagnostics);
+    if diagnostics[reported..].iter().any(|d| d.kind == DiagnosticKind::UnsupportedVersion) {
+        return Ok(None);
+    }
+    let backup_path = path.with_extension("ini.bak");
+    fs::copy(path, &backup_path)?;
+    fs::write(path, migrated)?;
+    Ok(Some(backup_path))
+}
@@ -0,0 +1,126 @@
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    bindings::{is_all_keybinding_keys_pressed, Keybinding},
+    config::Action,
+    keycode::Keycode,
+};
+
+// Tracks how far into each sequence bind ("G > 4") the player is. Every step has to be pressed
+// within `timeout` of the previous one and after the previous one is released, and any other key
+// press starts over
+pub struct SequenceMatcher {
+    timeout: Duration,
+    previous_pressed: Vec<Keycode>,
+    // (index into the keybindings, index of the next step to press)
+    partial: Vec<(usize, usize)>,
+    last_step_at: Instant,
+    // The keys of the step pressed last, kept away from the plain binds until they are released
+    consumed_keys: Option<HashSet<Keycode>>,
+}
+
+fn step_keys(keybinding: &Keybinding, step: usize) -> &[HashSet<Keycode>] {
+    match keybinding.leader.get(step) {
+        Some(alternatives) => alternatives,
+        None => std::slice::from_ref(&keybinding.keys),
+    }
+}
+
+// The key set of the step that went down this frame, if it did
+fn pressed_step<'a>(
+    keybinding: &'a Keybinding,
+    step: usize,
+    pressed_keys: &[Keycode],
+    previous_pressed: &[Keycode],
+) -> Option<&'a HashSet<Keycode>> {
+    step_keys(keybinding, step).iter().find(|keys| {
+        is_all_keybinding_keys_pressed(keys, pressed_keys) && !is_all_keybinding_keys_pressed(keys, previous_pressed)
+    })
+}
+
+impl SequenceMatcher {
+    pub fn new(timeout: Duration, now: Instant) -> Self {
+        SequenceMatcher {
+            timeout,
+            previous_pressed: Vec::new(),
+            partial: Vec::new(),
+            last_step_at: now,
+            consumed_keys: None,
+        }
+    }
+
+    // The keybinding indices change on a reload, so any sequence in progress is dropped
+    pub fn reset(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+        self.partial.clear();
+    }
+
+    // The keys that started, continued or finished a sequence while they are still down
+    pub fn consumed_keys(&mut self, pressed_keys: &[Keycode]) -> Option<&HashSet<Keycode>> {
+        if self.consumed_keys.as_ref().is_some_and(|keys| !is_all_keybinding_keys_pressed(keys, pressed_keys)) {
+            self.consumed_keys = None;
+        }
+        self.consumed_keys.as_ref()
+    }
+
+    // Returns the action of a sequence finished by this frame's key presses
+    // `is_available` leaves out sequences in layers that are switched off
+    pub fn advance(
+        &mut self,
+        keybindings: &[Keybinding],
+        is_available: impl Fn(&Keybinding) -> bool,
+        pressed_keys: &[Keycode],
+        now: Instant,
+    ) -> Option<Action> {
+        let previous_pressed = std::mem::replace(&mut self.previous_pressed, pressed_keys.to_vec());
+        // Holding G and pressing 4 is a different shortcut, not the sequence "G > 4"
+        let is_step_released = self.consumed_keys(pressed_keys).is_none();
+        if now.duration_since(self.last_step_at) > self.timeout {
+            self.partial.clear();
+        }
+
+        // Modifiers going down don't count as a step, Shift has to be down before the 4 of "G > Shift+4"
+        let is_new_press = pressed_keys.iter().any(|k| !k.is_modifier() && !previous_pressed.contains(k));
+        if !is_new_press {
+            return None;
+        }
+
+        let mut advanced = Vec::new();
+        let mut step_pressed = None;
+        for &(index, step) in self.partial.iter().filter(|_| is_step_released) {
+            let keybinding = &keybindings[index];
+            let Some(keys) = pressed_step(keybinding, step, pressed_keys, &previous_pressed) else {
+                continue;
+            };
+            if step == keybinding.leader.len() {
+                self.partial.clear();
+                self.consumed_keys = Some(keys.clone());
+                return Some(keybinding.action.clone());
+            }
+            advanced.push((index, step + 1));
+            step_pressed.get_or_insert(keys);
+        }
+
+        // A press that didn't continue any sequence may be the first step of another one
+        if advanced.is_empty() {
+            for (index, keybinding) in keybindings.iter().enumerate() {
+                if keybinding.leader.is_empty() || !is_available(keybinding) {
+                    continue;
+                }
+                if let Some(keys) = pressed_step(keybinding, 0, pressed_keys, &previous_pressed) {
+                    advanced.push((index, 1));
+                    step_pressed.get_or_insert(keys);
+                }
+            }
+        }
+        if let Some(keys) = step_pressed {
+            self.last_step_at = now;
+            self.consumed_keys = Some(keys.clone());
+        }
+        self.partial = advanced;
+        None
+    }
+}
@@ -0,0 +1,175 @@
+use std::{borrow::Cow, fmt::Display, str::FromStr};
+
+use keyboard_codes::{parse_key_ignore_case, parse_modifier_with_aliases, parser::normalize_key_name, Modifier};
+
+use crate::{
+    keycode::{parse_raw_code, Keycode},
+    mapper::{map_key, map_virtual_key},
+};
+
+// Keys keyboard_codes doesn't know about, written by these names in the ini
+const EXTRA_KEYS: &[(&str, Keycode)] = &[
+    ("Mouse4", Keycode::Mouse4),
+    ("Mouse5", Keycode::Mouse5),
+    ("WheelUp", Keycode::WheelUp),
+    ("WheelDown", Keycode::WheelDown),
+    // Punctuation by its US layout position, these can't be written as symbols since '-' separates keys
+    ("Grave", Keycode::Grave),
+    ("Backtick", Keycode::Grave),
+    ("Minus", Keycode::Minus),
+    ("Equal", Keycode::Equal),
+    ("Equals", Keycode::Equal),
+    ("LeftBracket", Keycode::LeftBracket),
+    ("RightBracket", Keycode::RightBracket),
+    ("Backslash", Keycode::BackSlash),
+    ("Semicolon", Keycode::Semicolon),
+    ("Apostrophe", Keycode::Apostrophe),
+    ("Quote", Keycode::Apostrophe),
+    ("Comma", Keycode::Comma),
+    ("Period", Keycode::Dot),
+    ("Dot", Keycode::Dot),
+    ("Slash", Keycode::Slash),
+    ("Oem102", Keycode::Oem102),
+    ("PrintScreen", Keycode::PrintScreen),
+    ("LB", Keycode::LeftBumper),
+    ("RB", Keycode::RightBumper),
+    ("LT", Keycode::LeftTrigger),
+    ("RT", Keycode::RightTrigger),
+    ("LS", Keycode::LeftStick),
+    ("RS", Keycode::RightStick),
+    ("DPadUp", Keycode::DPadUp),
+    ("DPadDown", Keycode::DPadDown),
+    ("DPadLeft", Keycode::DPadLeft),
+    ("DPadRight", Keycode::DPadRight),
+    ("PadA", Keycode::PadA),
+    ("PadB", Keycode::PadB),
+    ("PadX", Keycode::PadX),
+    ("PadY", Keycode::PadY),
+    ("Start", Keycode::Start),
+    ("View", Keycode::View),
+    // PlayStation names for the same buttons
+    ("L1", Keycode::LeftBumper),
+    ("R1", Keycode::RightBumper),
+    ("L2", Keycode::LeftTrigger),
+    ("R2", Keycode::RightTrigger),
+    ("L3", Keycode::LeftStick),
+    ("R3", Keycode::RightStick),
+    ("Cross", Keycode::PadA),
+    ("Circle", Keycode::PadB),
+    ("Square", Keycode::PadX),
+    ("Triangle", Keycode::PadY),
+    ("Options", Keycode::Start),
+    ("Share", Keycode::View),
+];
+
+// Modifiers and one key, like "Shift+1" or "Alt+Mouse4". The key is already mapped to the Keycode the
+// input sources report, so a key that can't be pressed is caught when the ini is read
+// Controller buttons can be held in front of the key the same way, "LB+DPadRight" has LB in `buttons`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Shortcut {
+    pub modifiers: Vec<Modifier>,
+    pub buttons: Vec<Keycode>,
+    pub key: Keycode,
+}
+
+fn parse_key(name: &str) -> Result<Keycode, String> {
+    if let Some((_, keycode)) = EXTRA_KEYS.iter().find(|(extra, _)| extra.eq_ignore_ascii_case(name)) {
+        return Ok(*keycode);
+    }
+    // VK_0x7C is bound as the F13 it names, so it matches however the key is reported
+    let is_raw_code = ["VK_", "SC_"].iter().any(|prefix| name.get(..3).is_some_and(|start| start.eq_ignore_ascii_case(prefix)));
+    if is_raw_code {
+        return match parse_raw_code(name) {
+            Some(Keycode::VirtualKey(code)) => map_virtual_key(code).ok_or_else(|| format!("'{}' can't be bound", name)),
+            Some(keycode) => Ok(keycode),
+            None => Err(format!("'{}' is not a virtual-key or scan code, write them like VK_0x7C or SC_0x1A", name)),
+        };
+    }
+    let key = parse_key_ignore_case(normalize_key_name(name)).map_err(|e| e.to_string())?;
+    map_key(&key).ok_or_else(|| format!("'{}' can't be bound", name))
+}
+
+// The name a key is written with in the ini, keyboard_codes' names where they differ from the Keycode ones
+pub fn key_name(keycode: Keycode) -> Cow<'static, str> {
+    let name = match keycode {
+        Keycode::Key0 => "0",
+        Keycode::Key1 => "1",
+        Keycode::Key2 => "2",
+        Keycode::Key3 => "3",
+        Keycode::Key4 => "4",
+        Keycode::Key5 => "5",
+        Keycode::Key6 => "6",
+        Keycode::Key7 => "7",
+        Keycode::Key8 => "8",
+        Keycode::Key9 => "9",
+        Keycode::Numpad0 => "Num0",
+        Keycode::Numpad1 => "Num1",
+        Keycode::Numpad2 => "Num2",
+        Keycode::Numpad3 => "Num3",
+        Keycode::Numpad4 => "Num4",
+        Keycode::Numpad5 => "Num5",
+        Keycode::Numpad6 => "Num6",
+        Keycode::Numpad7 => "Num7",
+        Keycode::Numpad8 => "Num8",
+        Keycode::Numpad9 => "Num9",
+        Keycode::NumpadMultiply => "NumMultiply",
+        Keycode::NumpadAdd => "NumAdd",
+        Keycode::NumpadSubtract => "NumSubtract",
+        Keycode::NumpadDivide => "NumDivide",
+        Keycode::NumpadDecimal => "NumDecimal",
+        Keycode::Up => "ArrowUp",
+        Keycode::Down => "ArrowDown",
+        Keycode::Left => "ArrowLeft",
+        Keycode::Right => "ArrowRight",
+        _ => return keycode.as_str(),
+    };
+    name.into()
+}
+
+// Same rules as keyboard_codes::parse_input: '+', '-' and spaces all separate the parts, the last part is the key
+impl FromStr for Shortcut {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("no shortcut given".to_string());
+        }
+        let normalized = s.replace(['-', ' '], "+");
+        let mut parts = normalized.split('+').map(str::trim).collect::<Vec<&str>>();
+        let key = parse_key(parts.pop().unwrap_or_default())?;
+        let mut modifiers = Vec::new();
+        let mut buttons = Vec::new();
+        for part in parts {
+            match parse_modifier_with_aliases(part) {
+                Ok(modifier) => modifiers.push(modifier),
+                Err(e) => match parse_key(part) {
+                    Ok(button) if button.is_gamepad_button() => buttons.push(button),
+                    _ => return Err(e.to_string()),
+                },
+            }
+        }
+        Ok(Shortcut { modifiers, buttons, key })
+    }
+}
+
+impl Shortcut {
+    // Like the ini form, but with the key named by `key_label` where it has a label, e.g. what the
+    // current keyboard layout prints on it
+    pub fn format_with(&self, key_label: impl Fn(Keycode) -> Option<String>) -> String {
+        let label = |keycode: Keycode| key_label(keycode).unwrap_or_else(|| key_name(keycode).into_owned());
+        self.modifiers.iter()
+            .map(Modifier::to_string)
+            .chain(self.buttons.iter().map(|button| label(*button)))
+            .chain(std::iter::once(label(self.key)))
+            .collect::<Vec<String>>()
+            .join("+")
+    }
+}
+
+// Formats the shortcut the way it is written in the ini
+impl Display for Shortcut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.format_with(|_| None))
+    }
+}
@@ -1,4 +1,3 @@
-#![allow(dead_code)]
 use std::{
     fs::{File, OpenOptions},
     io::Write,
@@ -16,6 +15,7 @@ impl SimpleLogger {
     pub fn new(log_path: &Path) -> Self {
         let file = OpenOptions::new()
             .create(true)
+            .truncate(true)
             .write(true)
             .open(log_path)
             .expect("Failed to open log file");
@@ -35,6 +35,10 @@ impl SimpleLogger {
         self.log("INFO", message);
     }
 
+    pub fn log_warning(&self, message: &str) {
+        self.log("WARN", message);
+    }
+
     pub fn log_debug(&self, message: &str) {
         self.log("DEBUG", message);
     }
@@ -0,0 +1,160 @@
+use crate::config::SlotBoundary;
+
+pub trait MagicSlots {
+    fn param_ids(&self) -> Vec<i32>;
+
+    fn selected_slot(&self) -> i32;
+
+    fn set_selected_slot(&mut self, slot: i32);
+}
+
+// Empty slots hold -1 or 0 rather than a spell
+pub fn is_filled(param_id: i32) -> bool {
+    param_id > 1
+}
+
+// Indices of the slots holding a spell, attuned spells can leave holes anywhere in the list
+pub fn filled_slots(equipped_magic: &impl MagicSlots) -> Vec<usize> {
+    equipped_magic.param_ids().iter()
+        .enumerate()
+        .filter(|(_, param_id)| is_filled(**param_id))
+        .map(|(slot, _)| slot)
+        .collect()
+}
+
+// Each of these returns false when it did nothing, so the HUD isn't shown for a press that was ignored.
+// Nothing happens when nothing is attuned
+
+// An empty target inside the list picks the closest filled slot before it unless `ignore_empty` is set,
+// a target past the last spell follows `past_end`
+pub fn set_memory_slot(equipped_magic: &mut impl MagicSlots, slot_index: u8, past_end: SlotBoundary, ignore_empty: bool) -> bool {
+    let filled = filled_slots(equipped_magic);
+    let target = usize::from(slot_index);
+    let (Some(first), Some(last)) = (filled.first(), filled.last()) else {
+        return false;
+    };
+
+    let slot = if filled.contains(&target) {
+        target
+    } else if ignore_empty {
+        return false;
+    } else if target > *last {
+        match past_end {
+            SlotBoundary::Wrap => filled[target % filled.len()],
+            SlotBoundary::Clamp => *last,
+            SlotBoundary::Ignore => return false,
+        }
+    } else {
+        filled.iter().rev().find(|slot| **slot < target).copied().unwrap_or(*first)
+    };
+
+    equipped_magic.set_selected_slot(slot as i32);
+    true
+}
+
+pub fn forward_cycle_memory_slot(equipped_magic: &mut impl MagicSlots, at_end: SlotBoundary) -> bool {
+    let filled = filled_slots(equipped_magic);
+    let selected = equipped_magic.selected_slot();
+    let next_slot = match filled.iter().find(|slot| **slot as i32 > selected) {
+        Some(slot) => Some(slot),
+        None => match at_end {
+            SlotBoundary::Wrap => filled.first(),
+            SlotBoundary::Clamp => filled.last(),
+            SlotBoundary::Ignore => None,
+        },
+    };
+
+    let Some(slot) = next_slot else {
+        return false;
+    };
+    equipped_magic.set_selected_slot(*slot as i32);
+    true
+}
+
+pub fn back_cycle_memory_slot(equipped_magic: &mut impl MagicSlots, at_start: SlotBoundary) -> bool {
+    let filled = filled_slots(equipped_magic);
+    let selected = equipped_magic.selected_slot();
+    let previous_slot = match filled.iter().rev().find(|slot| (**slot as i32) < selected) {
+        Some(slot) => Some(slot),
+        None => match at_start {
+            SlotBoundary::Wrap => filled.last(),
+            SlotBoundary::Clamp => filled.first(),
+            SlotBoundary::Ignore => None,
+        },
+    };
+
+    let Some(slot) = previous_slot else {
+        return false;
+    };
+    equipped_magic.set_selected_slot(*slot as i32);
+    true
+}
+
+// Cycles through `group`, slot indices in the order they are written in the ini, skipping the empty ones.
+// It carries on from the selected slot when that is in the group, otherwise from `cursor`, the slot the
+// group last selected, so going through another group or the D-pad doesn't lose the place
+pub fn forward_cycle_group(equipped_magic: &mut impl MagicSlots, group: &[usize], cursor: Option<usize>, at_end: SlotBoundary) -> bool {
+    let param_ids = equipped_magic.param_ids();
+    let is_group_slot_filled = |slot: &&usize| param_ids.get(**slot).is_some_and(|param_id| is_filled(*param_id));
+    let position = group_position(group, equipped_magic.selected_slot(), cursor);
+
+    let next_slot = match position {
+        Some(position) => group[position + 1..].iter().find(is_group_slot_filled),
+        None => group.iter().find(is_group_slot_filled),
+    };
+    let next_slot = next_slot.or_else(|| match at_end {
+        SlotBoundary::Wrap => group.iter().find(is_group_slot_filled),
+        SlotBoundary::Clamp => group.iter().rev().find(is_group_slot_filled),
+        SlotBoundary::Ignore => None,
+    });
+
+    let Some(slot) = next_slot else {
+        return false;
+    };
+    equipped_magic.set_selected_slot(*slot as i32);
+    true
+}
+
+pub fn back_cycle_group(equipped_magic: &mut impl MagicSlots, group: &[usize], cursor: Option<usize>, at_start: SlotBoundary) -> bool {
+    let param_ids = equipped_magic.param_ids();
+    let is_group_slot_filled = |slot: &&usize| param_ids.get(**slot).is_some_and(|param_id| is_filled(*param_id));
+    let position = group_position(group, equipped_magic.selected_slot(), cursor);
+
+    let previous_slot = match position {
+        Some(position) => group[..position].iter().rev().find(is_group_slot_filled),
+        None => group.iter().rev().find(is_group_slot_filled),
+    };
+    let previous_slot = previous_slot.or_else(|| match at_start {
+        SlotBoundary::Wrap => group.iter().rev().find(is_group_slot_filled),
+        SlotBoundary::Clamp => group.iter().find(is_group_slot_filled),
+        SlotBoundary::Ignore => None,
+    });
+
+    let Some(slot) = previous_slot else {
+        return false;
+    };
+    equipped_magic.set_selected_slot(*slot as i32);
+    true
+}
+
+fn group_position(group: &[usize], selected: i32, cursor: Option<usize>) -> Option<usize> {
+    group.iter()
+        .position(|slot| *slot as i32 == selected)
+        .or_else(|| cursor.and_then(|cursor| group.iter().position(|slot| *slot == cursor)))
+}
+
+// Selects `slot` if it still holds a spell
+pub fn select_filled_slot(equipped_magic: &mut impl MagicSlots, slot: usize) -> bool {
+    if !equipped_magic.param_ids().get(slot).is_some_and(|param_id| is_filled(*param_id)) {
+        return false;
+    }
+    equipped_magic.set_selected_slot(slot as i32);
+    true
+}
+
+// Returns the slot the spell was found in, None when it isn't attuned. Empty slots are never a match
+pub fn select_spell(equipped_magic: &mut impl MagicSlots, param_id: i32) -> Option<usize> {
+    let slot = equipped_magic.param_ids().iter().position(|id| is_filled(*id) && *id == param_id)?;
+    equipped_magic.set_selected_slot(slot as i32);
+    Some(slot)
+}
@@ -0,0 +1,105 @@
+use std::{fmt::Display, str::FromStr, sync::OnceLock};
+
+use crate::slots::is_filled;
+
+const MAGIC_PARAMS: &str = include_str!("../data/magic_params.tsv");
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpellType {
+    Sorcery,
+    Incantation,
+}
+
+impl Display for SpellType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpellType::Sorcery => write!(f, "sorcery"),
+            SpellType::Incantation => write!(f, "incantation"),
+        }
+    }
+}
+
+impl FromStr for SpellType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "sorcery" => Ok(SpellType::Sorcery),
+            "incantation" => Ok(SpellType::Incantation),
+            _ => Err(format!("'{}' is not a spell type, use sorcery or incantation", s.trim())),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Spell {
+    pub param_id: i32,
+    pub spell_type: SpellType,
+    pub name: &'static str,
+}
+
+// The spells in data/magic_params.tsv, in param ID order. Not every spell in the game is in there yet
+pub fn spells() -> &'static [Spell] {
+    static SPELLS: OnceLock<Vec<Spell>> = OnceLock::new();
+    SPELLS.get_or_init(|| {
+        MAGIC_PARAMS.lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let mut columns = line.split('\t');
+                Some(Spell {
+                    param_id: columns.next()?.trim().parse().ok()?,
+                    spell_type: columns.next()?.parse().ok()?,
+                    name: columns.next()?.trim(),
+                })
+            })
+            .collect()
+    })
+}
+
+// Ini keys are lowercased and people drop the apostrophes, so "lorettas greatbow" still finds Loretta's Greatbow
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+pub fn spell_by_param_id(param_id: i32) -> Option<&'static Spell> {
+    spells().iter().find(|spell| spell.param_id == param_id)
+}
+
+pub fn spell_by_name(name: &str) -> Option<&'static Spell> {
+    let name = normalize_name(name);
+    spells().iter().find(|spell| normalize_name(spell.name) == name)
+}
+
+// A spell written in the ini, either its English name or its Magic param ID. IDs missing from the
+// table are still accepted so new or modded spells can be bound, but not the IDs empty slots hold
+pub fn parse_spell(text: &str) -> Result<i32, String> {
+    if let Ok(param_id) = text.trim().parse::<i32>() {
+        if !is_filled(param_id) {
+            return Err(format!("{} is not a spell, empty slots hold -1 or 0", param_id));
+        }
+        return Ok(param_id);
+    }
+    spell_by_name(text)
+        .map(|spell| spell.param_id)
+        .ok_or_else(|| format!("'{}' is not a known spell", text.trim()))
+}
+
+// "Rock Sling", or the bare param ID when it isn't in the table
+pub fn spell_label(param_id: i32) -> String {
+    match spell_by_param_id(param_id) {
+        Some(spell) => spell.name.to_string(),
+        None => param_id.to_string(),
+    }
+}
+
+// "slot 4 (Comet Azur)", numbered from 1 like the memory_slot_<number> binds
+pub fn slot_label(param_ids: &[i32], slot: i32) -> String {
+    let spell = match usize::try_from(slot).ok().and_then(|slot| param_ids.get(slot)) {
+        Some(param_id) if is_filled(*param_id) => spell_label(*param_id),
+        _ => "empty".to_string(),
+    };
+    format!("slot {} ({})", slot + 1, spell)
+}
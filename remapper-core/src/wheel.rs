@@ -0,0 +1,74 @@
+use std::sync::{
+    atomic::{AtomicI32, Ordering},
+    Arc,
+};
+
+use crate::{
+    input::{InputFrame, InputSource},
+    keycode::Keycode,
+};
+
+// Wheel notches counted on whichever thread sees the scroll messages, positive for up. Polling only
+// gives button state, so the scrolling has to be collected as it happens and handed over here
+#[derive(Clone, Debug, Default)]
+pub struct WheelNotches(Arc<AtomicI32>);
+
+impl WheelNotches {
+    pub fn new() -> Self {
+        WheelNotches::default()
+    }
+
+    pub fn add(&self, notches: i32) {
+        self.0.fetch_add(notches, Ordering::Relaxed);
+    }
+
+    fn take(&self) -> i32 {
+        self.0.swap(0, Ordering::Relaxed)
+    }
+}
+
+// Adds WheelUp and WheelDown to the frames of another source. Each notch is down for one frame and up
+// for the next, so a fast scroll fires a bind once per notch instead of looking like one long press
+pub struct WheelInputSource<S> {
+    source: S,
+    notches: WheelNotches,
+    // Notches taken from `notches` that haven't been turned into a press yet
+    pending: i32,
+    is_pressed: bool,
+}
+
+impl<S: InputSource> WheelInputSource<S> {
+    pub fn new(source: S, notches: WheelNotches) -> Self {
+        WheelInputSource {
+            source,
+            notches,
+            pending: 0,
+            is_pressed: false,
+        }
+    }
+
+    fn add_notch(&mut self, mut frame: InputFrame) -> InputFrame {
+        self.pending += self.notches.take();
+
+        if self.is_pressed || self.pending == 0 {
+            self.is_pressed = false;
+            return frame;
+        }
+        frame.pressed_keys.push(if self.pending > 0 { Keycode::WheelUp } else { Keycode::WheelDown });
+        self.pending -= self.pending.signum();
+        self.is_pressed = true;
+        frame
+    }
+}
+
+impl<S: InputSource> InputSource for WheelInputSource<S> {
+    fn poll(&mut self) -> Option<InputFrame> {
+        let frame = self.source.poll()?;
+        Some(self.add_notch(frame))
+    }
+
+    fn poll_changes(&mut self) -> Vec<InputFrame> {
+        let frames = self.source.poll_changes();
+        frames.into_iter().map(|frame| self.add_notch(frame)).collect()
+    }
+}
@@ -0,0 +1,124 @@
+use std::{collections::HashSet, path::Path};
+
+use remapper_core::{
+    bindings::{expand_combinations, format_keys},
+    mapper::{map_scan_code, scan_code},
+    slots::{back_cycle_memory_slot, forward_cycle_memory_slot, set_memory_slot},
+    build_keybindings, load_config_from_str, Action, Keycode, MagicSlots, Shortcut, SlotBoundary,
+};
+
+struct Slots {
+    param_ids: Vec<i32>,
+    selected_slot: i32,
+}
+
+impl MagicSlots for Slots {
+    fn param_ids(&self) -> Vec<i32> {
+        self.param_ids.clone()
+    }
+
+    fn selected_slot(&self) -> i32 {
+        self.selected_slot
+    }
+
+    fn set_selected_slot(&mut self, slot: i32) {
+        self.selected_slot = slot;
+    }
+}
+
+#[test]
+fn generic_modifier_expands_to_both_sides() {
+    let shortcut = "Shift+1".parse::<Shortcut>().unwrap();
+    let expanded = expand_combinations(shortcut.key, shortcut.modifiers);
+
+    assert_eq!(expanded.len(), 2);
+    assert!(expanded.contains(&HashSet::from([Keycode::LShift, Keycode::Key1])));
+    assert!(expanded.contains(&HashSet::from([Keycode::RShift, Keycode::Key1])));
+}
+
+#[test]
+fn controller_buttons_hold_like_modifiers_but_keys_do_not() {
+    let shortcut = "l1+dpadright".parse::<Shortcut>().unwrap();
+    assert_eq!((shortcut.buttons, shortcut.key), (vec![Keycode::LeftBumper], Keycode::DPadRight));
+    assert_eq!("Shift+LT+PadA".parse::<Shortcut>().unwrap().to_string(), "Shift+LT+PadA");
+    assert!("G+4".parse::<Shortcut>().is_err());
+}
+
+#[test]
+fn raw_codes_bind_keys_without_a_name() {
+    let key = |text: &str| text.parse::<Shortcut>().map(|shortcut| shortcut.key);
+    assert_eq!(key("VK_0x7C"), Ok(Keycode::F13));
+    assert_eq!(key("vk_0xe9"), Ok(Keycode::VirtualKey(0xE9)));
+    assert_eq!(key("SC_0x1A"), Ok(Keycode::ScanCode(0x1A)));
+    assert_eq!("Shift+SC_0xE01D".parse::<Shortcut>().unwrap().to_string(), "Shift+SC_0xE01D");
+    assert_eq!(key("F24"), Ok(Keycode::F24));
+    assert_eq!(key("Equals"), Ok(Keycode::Equal));
+
+    assert!(key("VK_0x01").is_err());
+    assert!(key("SC_0x1FF").is_err());
+}
+
+#[test]
+fn physical_keys_follow_us_positions_and_display_layout_labels() {
+    assert_eq!(map_scan_code(0x10), Some(Keycode::Q));
+    assert_eq!(map_scan_code(0x02), Some(Keycode::Key1));
+    assert_eq!(map_scan_code(0xE04B), Some(Keycode::Left));
+    assert_eq!(map_scan_code(0x4B), Some(Keycode::Numpad4));
+    assert_eq!(scan_code(Keycode::A), Some(0x1E));
+
+    // An AZERTY keyboard prints A where a US one prints Q
+    let shortcut = "Shift+Q".parse::<Shortcut>().unwrap();
+    let azerty = |keycode| (keycode == Keycode::Q).then(|| "A".to_string());
+    assert_eq!(shortcut.format_with(azerty), "Shift+A");
+    assert_eq!(shortcut.to_string(), "Shift+Q");
+}
+
+#[test]
+fn cycling_wraps_around_filled_slots() {
+    let mut slots = Slots { param_ids: vec![4000, 4001, 4002, -1, -1], selected_slot: 2 };
+
+    forward_cycle_memory_slot(&mut slots, SlotBoundary::Wrap);
+    assert_eq!(slots.selected_slot, 0);
+
+    back_cycle_memory_slot(&mut slots, SlotBoundary::Wrap);
+    assert_eq!(slots.selected_slot, 2);
+
+    set_memory_slot(&mut slots, 7, SlotBoundary::Clamp, false);
+    assert_eq!(slots.selected_slot, 2);
+}
+
+#[test]
+fn shortcut_lists_and_repeated_keys_bind_every_shortcut() {
+    let config = load_config_from_str(
+        Path::new("eldenring_remapper.ini"),
+        "[keybinds]\nmemory_slot_1 = Shift+1, Num1\nmemory_slot_1 = F1\nmemory_slot_2 = Shfit+2, Num2\n",
+    );
+
+    let shortcuts = |action: Action| config.keybinds.iter()
+        .filter(|k| k.action == action)
+        .map(|k| k.shortcut.to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(shortcuts(Action::SetMemorySlot(1)), ["Shift+1", "Num1", "F1"]);
+    assert_eq!(shortcuts(Action::SetMemorySlot(2)), ["Num2"]);
+    assert_eq!(config.diagnostics.len(), 1);
+
+    let keybindings = build_keybindings(&config.keybinds);
+    let bound_to = |keys: HashSet<Keycode>| keybindings.iter().find(|k| k.keys == keys).map(|k| k.action.clone());
+    assert_eq!(bound_to(HashSet::from([Keycode::Numpad1])), Some(Action::SetMemorySlot(1)));
+    assert_eq!(bound_to(HashSet::from([Keycode::F1])), Some(Action::SetMemorySlot(1)));
+}
+
+#[test]
+fn side_specific_and_priority_binds_come_first() {
+    let config = load_config_from_str(
+        Path::new("eldenring_remapper.ini"),
+        "[keybinds]\nmemory_slot_1 = Shift+1\nmemory_slot_11 = RightShift+1\ncycle_back = 1 priority=1\n",
+    );
+
+    let order = build_keybindings(&config.keybinds).into_iter()
+        .map(|k| (k.action, format_keys(&k.keys)))
+        .collect::<Vec<(Action, String)>>();
+    assert_eq!(order[0], (Action::CycleBack, "Key1".to_string()));
+    assert_eq!(order[1], (Action::SetMemorySlot(11), "RShift+Key1".to_string()));
+    assert!(order[2..].iter().all(|(action, _)| *action == Action::SetMemorySlot(1)));
+}
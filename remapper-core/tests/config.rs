@@ -0,0 +1,95 @@
+use std::path::Path;
+
+use remapper_core::{
+    config::{DEFAULT_CYCLE_DEBOUNCE_MILLISECONDS, DEFAULT_KEYBINDS},
+    load_config,
+    load_config_from_str,
+    DiagnosticKind,
+    LoadedConfig,
+};
+
+fn config_for(ini: &str) -> LoadedConfig {
+    load_config_from_str(Path::new("eldenring_remapper.ini"), ini)
+}
+
+fn bind_for(config: &LoadedConfig, ini_key: &str) -> Option<String> {
+    config.keybinds.iter().find(|keybind| keybind.ini_key == ini_key).map(|keybind| keybind.format_bind())
+}
+
+#[test]
+fn bad_binds_are_reported_on_their_line() {
+    let config = config_for(
+        "[keybinds]\nmemory_slot_x = 1\nmemory_slot_0 = 2\nmemory_slot_1 = Shift+Nope\nswap_to_nowhere = 3\n",
+    );
+
+    assert_eq!(
+        config.diagnostics.iter().map(|d| (d.kind, d.line, d.key.as_deref().unwrap_or_default())).collect::<Vec<_>>(),
+        vec![
+            (DiagnosticKind::InvalidMemorySlot, Some(2), "memory_slot_x"),
+            (DiagnosticKind::InvalidMemorySlot, Some(3), "memory_slot_0"),
+            (DiagnosticKind::InvalidShortcut, Some(4), "memory_slot_1"),
+            (DiagnosticKind::UnknownAction, Some(5), "swap_to_nowhere"),
+        ],
+    );
+    assert_eq!(config.diagnostics[0].reason, "'x' is not a memory slot number, ignoring it");
+    assert_eq!(config.diagnostics[1].reason, "memory slots are numbered from 1, ignoring it");
+    assert_eq!(bind_for(&config, "memory_slot_x"), None);
+    assert_eq!(bind_for(&config, "memory_slot_0"), None);
+}
+
+#[test]
+fn unparsable_shortcut_falls_back_to_the_default() {
+    let config = config_for(
+        "[keybinds]\nmemory_slot_1 = Shift+Nope\ncycle_forward = Nope, F1\n",
+    );
+
+    assert_eq!(bind_for(&config, "memory_slot_1").as_deref(), Some("Shift+1"));
+    assert!(config.diagnostics[0].reason.ends_with("using the default Shift+1"));
+    // The default only stands in when nothing in the list could be read
+    assert_eq!(bind_for(&config, "cycle_forward").as_deref(), Some("F1"));
+    assert!(config.diagnostics[1].reason.ends_with("ignoring it"));
+    assert_eq!(config.diagnostics.len(), 2);
+}
+
+#[test]
+fn defaults_only_fill_in_for_a_missing_keybinds_section() {
+    // Actions left out of [keybinds] stay unbound rather than picking up their default
+    let config = config_for("[keybinds]\nmemory_slot_1 = F1\n");
+    assert_eq!(config.keybinds.len(), 1);
+    assert_eq!(bind_for(&config, "memory_slot_1").as_deref(), Some("F1"));
+    assert_eq!(bind_for(&config, "memory_slot_2"), None);
+    assert!(config.diagnostics.is_empty());
+
+    let config = config_for("[settings]\nexact_modifiers = true\n");
+    assert_eq!(config.keybinds.len(), DEFAULT_KEYBINDS.len());
+    assert_eq!(config.diagnostics[0].reason, "no [keybinds] section, using the default keybinds");
+}
+
+#[test]
+fn missing_file_loads_the_defaults() {
+    let config = load_config(Path::new("remapper_core_config_that_does_not_exist.ini"));
+
+    assert_eq!(config.diagnostics.len(), 1);
+    assert_eq!((config.diagnostics[0].kind, config.diagnostics[0].line), (DiagnosticKind::UnreadableFile, None));
+    assert_eq!(config.keybinds.len(), DEFAULT_KEYBINDS.len());
+    for (ini_key, shortcut) in DEFAULT_KEYBINDS {
+        assert_eq!(bind_for(&config, ini_key).as_deref(), Some(*shortcut));
+    }
+    assert_eq!(config.other_settings.cycle_debouce_milliseconds, DEFAULT_CYCLE_DEBOUNCE_MILLISECONDS);
+    assert!(config.layers.is_empty() && !config.is_outdated());
+}
+
+#[test]
+fn shadowed_and_outranked_binds_are_info_not_warnings() {
+    let config = config_for(
+        "[keybinds]\nmemory_slot_1 = Shift+Nope\nmemory_slot_2 = F4\nmemory_slot_3 = Ctrl+F4\n",
+    );
+
+    assert_eq!(
+        config.diagnostics.iter().map(|d| (d.kind, d.kind.is_warning())).collect::<Vec<_>>(),
+        vec![(DiagnosticKind::InvalidShortcut, true), (DiagnosticKind::ShadowedShortcut, false)],
+    );
+    assert!(DiagnosticKind::UnreadableFile.is_warning());
+    assert!(DiagnosticKind::DuplicateShortcut.is_warning());
+    assert!(!DiagnosticKind::OutrankedShortcut.is_warning());
+}
@@ -0,0 +1,91 @@
+use std::path::Path;
+
+use remapper_core::{load_config_from_str, Diagnostic, DiagnosticKind};
+
+fn conflicts_for(ini: &str) -> Vec<Diagnostic> {
+    load_config_from_str(Path::new("eldenring_remapper.ini"), ini).diagnostics.into_iter()
+        .filter(|d| matches!(
+            d.kind,
+            DiagnosticKind::DuplicateShortcut | DiagnosticKind::OverlappingShortcut |
+                DiagnosticKind::OutrankedShortcut | DiagnosticKind::ShadowedShortcut
+        ))
+        .collect()
+}
+
+fn kinds_and_keys(conflicts: &[Diagnostic]) -> Vec<(DiagnosticKind, &str)> {
+    conflicts.iter().map(|d| (d.kind, d.key.as_deref().unwrap_or_default())).collect()
+}
+
+#[test]
+fn press_and_held_binds_on_the_same_keys_both_fire() {
+    let conflicts = conflicts_for(
+        "[keybinds]\nmemory_slot_1 = 3\ncycle_forward = 3\n",
+    );
+    assert_eq!(kinds_and_keys(&conflicts), vec![(DiagnosticKind::DuplicateShortcut, "cycle_forward")]);
+    assert!(conflicts[0].reason.contains("one press runs both"));
+
+    // tap: and hold: never fire on the same press, so they can share a key
+    let conflicts = conflicts_for(
+        "[keybinds]\ncycle_forward = tap:3\nmemory_slot_1 = hold:3\ncycle_back = double:3\n",
+    );
+    assert_eq!(kinds_and_keys(&conflicts), vec![]);
+}
+
+#[test]
+fn same_shortcut_twice_is_a_duplicate() {
+    let conflicts = conflicts_for(
+        "[keybinds]\ncycle_forward = F1\ncycle_back = F1\nmemory_slot_1 = Shift+F2\nmemory_slot_2 = shift+f2\n",
+    );
+    assert_eq!(
+        kinds_and_keys(&conflicts),
+        vec![(DiagnosticKind::DuplicateShortcut, "cycle_back"), (DiagnosticKind::DuplicateShortcut, "memory_slot_2")],
+    );
+    assert!(conflicts[0].reason.contains("cycle_forward wins because it comes first"));
+    assert_eq!(conflicts[0].line, Some(3));
+}
+
+#[test]
+fn either_side_modifiers_overlap_the_side_specific_ones() {
+    // Shift+F2 also matches LeftShift+F2 and RightShift+F2, which are side specific and win
+    let conflicts = conflicts_for(
+        "[keybinds]\nmemory_slot_1 = Shift+F2\nmemory_slot_2 = LeftShift+F2\nmemory_slot_3 = Ctrl+F3\nmemory_slot_4 = RightControl+F3 priority=-1\n",
+    );
+    assert_eq!(
+        kinds_and_keys(&conflicts),
+        vec![(DiagnosticKind::OutrankedShortcut, "memory_slot_1"), (DiagnosticKind::OutrankedShortcut, "memory_slot_4")],
+    );
+    assert!(conflicts[0].reason.contains("on LShift+F2 because its modifiers are side specific"));
+    assert!(conflicts[1].reason.contains("on RControl+F3 because it has a higher priority"));
+    assert!(conflicts.iter().all(|d| !d.kind.is_warning()));
+}
+
+#[test]
+fn longer_binds_shadow_shorter_ones_unless_outranked() {
+    let conflicts = conflicts_for(
+        "[keybinds]\nmemory_slot_5 = F4\nmemory_slot_6 = Ctrl+F4 priority=5\nmemory_slot_7 = F5 priority=5\nmemory_slot_8 = Ctrl+F5\n",
+    );
+    assert_eq!(
+        kinds_and_keys(&conflicts),
+        vec![(DiagnosticKind::ShadowedShortcut, "memory_slot_5"), (DiagnosticKind::OutrankedShortcut, "memory_slot_8")],
+    );
+    assert!(conflicts[0].reason.contains("holding LControl+F4 fires memory_slot_6 instead"));
+    assert!(conflicts[1].reason.contains("holding LControl+F5 fires memory_slot_7 instead"));
+}
+
+#[test]
+fn distinct_shortcuts_do_not_conflict() {
+    // Sequences and layers only compete among themselves, so these share keys without conflicting either
+    let conflicts = conflicts_for(
+        "[keybinds]\nmemory_slot_1 = F6\nmemory_slot_2 = F7\nmemory_slot_3 = G > F6\ncycle_forward = Ctrl+Alt+F8\n\n[layer.casting]\nactivate = hold:F9\ncycle_back = F6\n",
+    );
+    assert_eq!(kinds_and_keys(&conflicts), vec![]);
+}
+
+#[test]
+fn plain_binds_on_a_sequence_leader_are_shadowed() {
+    let conflicts = conflicts_for(
+        "[keybinds]\nmemory_slot_1 = G\nmemory_slot_2 = G > 4\n\n[layer.casting]\nactivate = hold:F9\ncycle_back = G\n",
+    );
+    assert_eq!(kinds_and_keys(&conflicts), vec![(DiagnosticKind::ShadowedShortcut, "memory_slot_1")]);
+    assert!(conflicts[0].reason.contains("is shadowed by memory_slot_2 (G > 4, line 3), pressing G starts that sequence instead"));
+}
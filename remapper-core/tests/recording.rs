@@ -0,0 +1,161 @@
+use std::{
+    sync::mpsc::Sender,
+    time::{Duration, Instant},
+};
+
+use remapper_core::{
+    EventInputSource,
+    InputFrame,
+    InputSource,
+    KeyEvent,
+    KeyEventHook,
+    Keycode,
+    RecordingInputSource,
+    ReplayInputSource,
+    WheelInputSource,
+    WheelNotches,
+};
+
+struct ScriptedInputSource {
+    frames: Vec<InputFrame>,
+}
+
+impl InputSource for ScriptedInputSource {
+    fn poll(&mut self) -> Option<InputFrame> {
+        if self.frames.is_empty() {
+            return None;
+        }
+        Some(self.frames.remove(0))
+    }
+}
+
+#[test]
+fn replay_reproduces_recorded_frames() {
+    let started = Instant::now();
+    let frames = vec![
+        InputFrame { timestamp: started, pressed_keys: vec![] },
+        InputFrame { timestamp: started + Duration::from_millis(16), pressed_keys: vec![Keycode::LShift, Keycode::Key1] },
+        InputFrame { timestamp: started + Duration::from_millis(33), pressed_keys: vec![Keycode::Key3] },
+    ];
+    let path = std::env::temp_dir().join(format!("remapper_core_recording_{}.rec", std::process::id()));
+
+    let mut recording = RecordingInputSource::create(ScriptedInputSource { frames: frames.clone() }, &path).unwrap();
+    while recording.poll().is_some() {}
+    drop(recording);
+
+    let mut replay = ReplayInputSource::open(&path, started).unwrap();
+    let mut replayed = Vec::new();
+    while let Some(frame) = replay.poll() {
+        replayed.push(frame);
+    }
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(replayed, frames);
+}
+
+#[test]
+fn wheel_notches_press_once_each() {
+    let started = Instant::now();
+    let frames = (0..6)
+        .map(|frame| InputFrame { timestamp: started + Duration::from_millis(16 * frame), pressed_keys: vec![Keycode::LShift] })
+        .collect();
+    let notches = WheelNotches::new();
+    let mut wheel = WheelInputSource::new(ScriptedInputSource { frames }, notches.clone());
+
+    notches.add(2);
+    let mut pressed = Vec::new();
+    while let Some(frame) = wheel.poll() {
+        pressed.push(frame.pressed_keys);
+        if pressed.len() == 4 {
+            notches.add(-1);
+        }
+    }
+
+    let shift_and = |keycode| vec![Keycode::LShift, keycode];
+    assert_eq!(pressed, vec![
+        shift_and(Keycode::WheelUp),
+        vec![Keycode::LShift],
+        shift_and(Keycode::WheelUp),
+        vec![Keycode::LShift],
+        shift_and(Keycode::WheelDown),
+        vec![Keycode::LShift],
+    ]);
+}
+
+struct ChannelHook<'a>(&'a mut Option<Sender<KeyEvent>>);
+
+impl KeyEventHook for ChannelHook<'_> {
+    fn start(self, events: Sender<KeyEvent>) -> Result<(), String> {
+        *self.0 = Some(events);
+        Ok(())
+    }
+}
+
+fn pressed_keys_and_timestamps(frames: Vec<InputFrame>) -> Vec<(Vec<Keycode>, Instant)> {
+    frames.into_iter().map(|frame| (frame.pressed_keys, frame.timestamp)).collect()
+}
+
+#[test]
+fn taps_between_frames_come_out_in_order_with_their_timestamps() {
+    let mut sender = None;
+    let mut events = EventInputSource::start(ChannelHook(&mut sender)).unwrap();
+    let sender = sender.unwrap();
+    let started = Instant::now();
+    let at = |milliseconds| started + Duration::from_millis(milliseconds);
+    let send = |keycode, is_down, milliseconds| sender.send(KeyEvent { keycode, is_down, timestamp: at(milliseconds) }).unwrap();
+
+    // 3 is tapped twice before the next frame, the second press isn't lost to the first
+    send(Keycode::Key3, true, 1);
+    send(Keycode::Key3, false, 2);
+    send(Keycode::Key3, true, 3);
+    send(Keycode::Key3, false, 4);
+    let frames = events.poll_changes();
+    assert_eq!(
+        pressed_keys_and_timestamps(frames[..4].to_vec()),
+        vec![
+            (vec![Keycode::Key3], at(1)),
+            (vec![], at(2)),
+            (vec![Keycode::Key3], at(3)),
+            (vec![], at(4)),
+        ]
+    );
+    // Then the state right now
+    assert_eq!(frames.len(), 5);
+    assert!(frames[4].pressed_keys.is_empty() && frames[4].timestamp >= at(4));
+
+    // Nothing happened since, so only the state right now
+    let frames = events.poll_changes();
+    assert_eq!(frames.len(), 1);
+    assert!(frames[0].pressed_keys.is_empty());
+}
+
+#[test]
+fn held_key_tapped_again_between_frames_goes_up_and_down() {
+    let mut sender = None;
+    let mut events = EventInputSource::start(ChannelHook(&mut sender)).unwrap();
+    let sender = sender.unwrap();
+    let started = Instant::now();
+    let at = |milliseconds| started + Duration::from_millis(milliseconds);
+    let send = |keycode, is_down, milliseconds| sender.send(KeyEvent { keycode, is_down, timestamp: at(milliseconds) }).unwrap();
+    let mut poll = || pressed_keys_and_timestamps(events.poll_changes());
+
+    send(Keycode::LShift, true, 1);
+    send(Keycode::Key3, true, 2);
+    assert_eq!(poll()[..2], [(vec![Keycode::LShift], at(1)), (vec![Keycode::LShift, Keycode::Key3], at(2))]);
+
+    // 3 is let go and pressed again within one frame, with key repeat on Shift in between
+    send(Keycode::LShift, true, 20);
+    send(Keycode::Key3, false, 21);
+    send(Keycode::LShift, true, 22);
+    send(Keycode::Key3, true, 23);
+    let frames = poll();
+    assert_eq!(frames[..2], [(vec![Keycode::LShift], at(21)), (vec![Keycode::LShift, Keycode::Key3], at(23))]);
+    assert_eq!(frames.len(), 3);
+
+    // One key reported under two names at once is a single change
+    send(Keycode::Key5, true, 40);
+    send(Keycode::ScanCode(0x06), true, 40);
+    let frames = poll();
+    assert_eq!(frames[0], (vec![Keycode::LShift, Keycode::Key3, Keycode::Key5, Keycode::ScanCode(0x06)], at(40)));
+    assert_eq!(frames.len(), 2);
+}
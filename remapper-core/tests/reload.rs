@@ -0,0 +1,70 @@
+use std::{
+    fs::File,
+    path::Path,
+    time::{Duration, Instant, SystemTime},
+};
+
+use remapper_core::{describe_config_changes, load_config_from_str, ConfigWatcher, LoadedConfig};
+
+// Sets the modified time by hand, a rewrite within the filesystem's timestamp resolution would look unchanged
+fn write_config(path: &Path, ini: &str, modified: SystemTime) {
+    std::fs::write(path, ini).unwrap();
+    File::options().write(true).open(path).unwrap().set_modified(modified).unwrap();
+}
+
+fn config_for(ini: &str) -> LoadedConfig {
+    load_config_from_str(Path::new("eldenring_remapper.ini"), ini)
+}
+
+#[test]
+fn watcher_reloads_once_per_change_and_at_most_once_a_second() {
+    let path = std::env::temp_dir().join("remapper_core_watched.ini");
+    let saved = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    write_config(&path, "[keybinds]\ncycle_forward = F1\n", saved);
+    let started = Instant::now();
+    let at = |milliseconds| started + Duration::from_millis(milliseconds);
+    let mut watcher = ConfigWatcher::new(&path, started);
+
+    assert!(watcher.poll(at(1000)).is_none());
+
+    // Saved twice in quick succession, only read once the second has passed
+    write_config(&path, "[keybinds]\ncycle_forward = F2\n", saved + Duration::from_secs(1));
+    assert!(watcher.poll(at(1500)).is_none());
+    write_config(&path, "[keybinds]\ncycle_forward = F3\n", saved + Duration::from_secs(2));
+    let config = watcher.poll(at(2000)).unwrap();
+    assert!(config.keybinds.iter().any(|keybind| keybind.ini_key == "cycle_forward" && keybind.format_bind() == "F3"));
+    assert!(watcher.poll(at(3000)).is_none());
+
+    // Deleted mid save, the current config is kept until the file is back
+    std::fs::remove_file(&path).unwrap();
+    assert!(watcher.poll(at(4000)).is_none());
+    write_config(&path, "[keybinds]\ncycle_forward = F4\n", saved + Duration::from_secs(3));
+    assert!(watcher.poll(at(5000)).is_some());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn changes_list_binds_layers_and_settings() {
+    let old = config_for(
+        "[keybinds]\ncycle_forward = F1\n\n[settings]\nexact_modifiers = false\n\n\
+         [layer.casting]\nactivate = hold:F9\ncycle_back = F6\n\n[layer.numbers]\nactivate = toggle:F10\nmemory_slot_1 = 1\n",
+    );
+    let new = config_for(
+        "[keybinds]\ncycle_forward = F2\n\n[settings]\nexact_modifiers = true\n\n\
+         [layer.casting]\nactivate = toggle:F9\ncycle_back = F6\n\n[layer.items]\nactivate = oneshot:F11\nmemory_slot_2 = 2\n",
+    );
+
+    assert_eq!(
+        describe_config_changes(&old.keybinds, &old.layers, &old.other_settings, &new.keybinds, &new.layers, &new.other_settings),
+        vec![
+            "cycle_forward: F1 -> F2",
+            "layer.numbers.memory_slot_1: 1 -> unbound",
+            "layer.items.memory_slot_2: unbound -> 2",
+            "layer.casting.activate: hold:F9 -> toggle:F9",
+            "layer.numbers.activate: toggle:F10 -> unset",
+            "layer.items.activate: unset -> oneshot:F11",
+            "exact_modifiers: false -> true",
+        ],
+    );
+    assert!(describe_config_changes(&new.keybinds, &new.layers, &new.other_settings, &new.keybinds, &new.layers, &new.other_settings).is_empty());
+}
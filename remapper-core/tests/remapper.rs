@@ -0,0 +1,361 @@
+use std::{
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use remapper_core::{build_keybindings, build_layers, load_config_from_str, FakeGamepad, GamepadBackend, GamepadState, GameState, Keycode, MagicSlots, Remapper};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Hud {
+    Off,
+    On,
+}
+
+struct Game {
+    param_ids: Vec<i32>,
+    selected_slot: i32,
+    hud_type: Hud,
+}
+
+impl MagicSlots for Game {
+    fn param_ids(&self) -> Vec<i32> {
+        self.param_ids.clone()
+    }
+
+    fn selected_slot(&self) -> i32 {
+        self.selected_slot
+    }
+
+    fn set_selected_slot(&mut self, slot: i32) {
+        self.selected_slot = slot;
+    }
+}
+
+impl GameState for Game {
+    type HudType = Hud;
+
+    const HUD_ON: Hud = Hud::On;
+
+    fn player_hp(&self) -> i32 {
+        100
+    }
+
+    fn hud_type(&self) -> Hud {
+        self.hud_type
+    }
+
+    fn set_hud_type(&mut self, hud_type: Hud) {
+        self.hud_type = hud_type;
+    }
+}
+
+fn remapper_for(ini: &str, now: Instant) -> Remapper<Hud> {
+    let config = load_config_from_str(Path::new("eldenring_remapper.ini"), ini);
+    Remapper::new(build_keybindings(&config.keybinds), build_layers(&config.layers), &config.other_settings, now)
+}
+
+fn new_game() -> Game {
+    Game { param_ids: vec![4000, 4010, 4020, 4030], selected_slot: 0, hud_type: Hud::Off }
+}
+
+#[test]
+fn held_slot_key_only_fires_once_and_lets_the_hud_restore() {
+    let started = Instant::now();
+    let mut remapper = remapper_for("[keybinds]\nmemory_slot_2 = Shift+2\n", started);
+    let mut game = new_game();
+    let held = [Keycode::LShift, Keycode::Key2];
+
+    remapper.run_frame(&mut game, &held, started);
+    assert_eq!((game.selected_slot, game.hud_type), (1, Hud::On));
+
+    // The game moving the cursor while the keys stay down isn't overridden
+    game.set_selected_slot(3);
+    for frame in 1..=250 {
+        remapper.run_frame(&mut game, &held, started + Duration::from_millis(16 * frame));
+    }
+    assert_eq!((game.selected_slot, game.hud_type), (3, Hud::Off));
+}
+
+#[test]
+fn release_trigger_fires_when_the_keys_go_up() {
+    let started = Instant::now();
+    let mut remapper = remapper_for(
+        "[keybinds]\nmemory_slot_3 = release:4\nmemory_slot_2 = press:4\n",
+        started,
+    );
+    let mut game = new_game();
+
+    remapper.run_frame(&mut game, &[Keycode::Key4], started);
+    assert_eq!(game.selected_slot, 1);
+    remapper.run_frame(&mut game, &[Keycode::Key4], started + Duration::from_millis(16));
+    assert_eq!(game.selected_slot, 1);
+    remapper.run_frame(&mut game, &[], started + Duration::from_millis(32));
+    assert_eq!(game.selected_slot, 2);
+}
+
+#[test]
+fn tap_hold_and_double_tap_share_a_key() {
+    let started = Instant::now();
+    let mut remapper = remapper_for(
+        "[keybinds]\ncycle_forward = tap:3\nmemory_slot_1 = hold(300ms):3\ncycle_back = double:3\n",
+        started,
+    );
+    let mut game = new_game();
+    game.set_selected_slot(2);
+    let mut run_until = |game: &mut Game, keys: &[Keycode], from: u64, to: u64| {
+        for milliseconds in (from..to).step_by(10) {
+            remapper.run_frame(game, keys, started + Duration::from_millis(milliseconds));
+        }
+    };
+
+    // A tap only fires once the double tap window has passed
+    run_until(&mut game, &[Keycode::Key3], 0, 100);
+    run_until(&mut game, &[], 100, 300);
+    assert_eq!(game.selected_slot, 2);
+    run_until(&mut game, &[], 300, 400);
+    assert_eq!(game.selected_slot, 3);
+
+    run_until(&mut game, &[Keycode::Key3], 400, 800);
+    assert_eq!(game.selected_slot, 0);
+    run_until(&mut game, &[], 800, 1200);
+    assert_eq!(game.selected_slot, 0);
+
+    run_until(&mut game, &[Keycode::Key3], 1200, 1250);
+    run_until(&mut game, &[], 1250, 1300);
+    run_until(&mut game, &[Keycode::Key3], 1300, 1350);
+    run_until(&mut game, &[], 1350, 1800);
+    assert_eq!(game.selected_slot, 3);
+}
+
+#[test]
+fn sequence_fires_within_the_timeout_and_hides_the_plain_bind() {
+    let started = Instant::now();
+    let mut remapper = remapper_for(
+        "[keybinds]\nmemory_slot_4 = G > 4\ncycle_forward = 4\n",
+        started,
+    );
+    let mut game = new_game();
+    let at = |milliseconds: u64| started + Duration::from_millis(milliseconds);
+
+    remapper.run_frame(&mut game, &[Keycode::G], at(0));
+    remapper.run_frame(&mut game, &[], at(100));
+    remapper.run_frame(&mut game, &[Keycode::Key4], at(200));
+    remapper.run_frame(&mut game, &[Keycode::Key4], at(600));
+    assert_eq!(game.selected_slot, 3);
+    remapper.run_frame(&mut game, &[], at(700));
+
+    // Too slow, so the 4 is just the plain cycle bind
+    remapper.run_frame(&mut game, &[Keycode::G], at(1000));
+    remapper.run_frame(&mut game, &[], at(1100));
+    remapper.run_frame(&mut game, &[Keycode::Key4], at(2500));
+    assert_eq!(game.selected_slot, 0);
+}
+
+#[test]
+fn sequence_steps_need_the_previous_one_released() {
+    let started = Instant::now();
+    let mut remapper = remapper_for(
+        "[keybinds]\nmemory_slot_4 = G > 4\nmemory_slot_2 = G\nmemory_slot_3 = Shift+G\n",
+        started,
+    );
+    let mut game = new_game();
+    let at = |milliseconds: u64| started + Duration::from_millis(milliseconds);
+
+    // G starts the sequence instead of firing its plain bind, and 4 pressed while G is still down doesn't finish it
+    remapper.run_frame(&mut game, &[Keycode::G], at(0));
+    remapper.run_frame(&mut game, &[Keycode::G, Keycode::Key4], at(100));
+    remapper.run_frame(&mut game, &[], at(200));
+    assert_eq!(game.selected_slot, 0);
+    remapper.run_frame(&mut game, &[Keycode::Key4], at(300));
+    assert_eq!(game.selected_slot, 0);
+    remapper.run_frame(&mut game, &[], at(400));
+
+    // Released in the same frame 4 goes down still counts, the frame only has to see G gone
+    remapper.run_frame(&mut game, &[Keycode::G], at(500));
+    remapper.run_frame(&mut game, &[Keycode::Key4], at(600));
+    assert_eq!(game.selected_slot, 3);
+    remapper.run_frame(&mut game, &[], at(700));
+
+    // Other binds that include G still fire
+    remapper.run_frame(&mut game, &[Keycode::LShift, Keycode::G], at(800));
+    assert_eq!(game.selected_slot, 2);
+}
+
+#[test]
+fn layers_repurpose_plain_keys_while_active() {
+    let started = Instant::now();
+    let mut remapper = remapper_for(
+        "[keybinds]\ncycle_forward = 2\n\
+         [layer.numbers]\nactivate = hold:CapsLock\nmemory_slot_2 = 2\nmemory_slot_4 = 4\n\
+         [layer.once]\nactivate = oneshot:Tab\nmemory_slot_1 = 2\n",
+        started,
+    );
+    let mut game = new_game();
+    let at = |milliseconds: u64| started + Duration::from_millis(milliseconds);
+
+    remapper.run_frame(&mut game, &[Keycode::Key4], at(0));
+    assert_eq!(game.selected_slot, 0);
+
+    remapper.run_frame(&mut game, &[Keycode::CapsLock], at(100));
+    remapper.run_frame(&mut game, &[Keycode::CapsLock, Keycode::Key4], at(200));
+    assert_eq!(game.selected_slot, 3);
+    remapper.run_frame(&mut game, &[Keycode::CapsLock, Keycode::Key2], at(300));
+    assert_eq!(game.selected_slot, 1);
+
+    // Released, so 2 falls back to cycling forward
+    remapper.run_frame(&mut game, &[], at(400));
+    remapper.run_frame(&mut game, &[Keycode::Key2], at(500));
+    assert_eq!(game.selected_slot, 2);
+    remapper.run_frame(&mut game, &[], at(600));
+
+    // A one-shot layer only lasts for the next bind
+    remapper.run_frame(&mut game, &[Keycode::Tab], at(700));
+    remapper.run_frame(&mut game, &[], at(800));
+    remapper.run_frame(&mut game, &[Keycode::Key2], at(900));
+    assert_eq!(game.selected_slot, 0);
+    remapper.run_frame(&mut game, &[], at(1000));
+    remapper.run_frame(&mut game, &[Keycode::Key2], at(1100));
+    assert_eq!(game.selected_slot, 1);
+}
+
+#[test]
+fn exact_binds_ignore_extra_modifiers_but_not_movement_keys() {
+    let started = Instant::now();
+    let mut remapper = remapper_for(
+        "[keybinds]\nmemory_slot_2 = exact:Shift+2\nmemory_slot_3 = Shift+3\n",
+        started,
+    );
+    let mut game = new_game();
+    let at = |milliseconds: u64| started + Duration::from_millis(milliseconds);
+
+    remapper.run_frame(&mut game, &[Keycode::LControl, Keycode::LShift, Keycode::Key2], at(0));
+    assert_eq!(game.selected_slot, 0);
+    remapper.run_frame(&mut game, &[Keycode::W, Keycode::LShift, Keycode::Key2], at(100));
+    assert_eq!(game.selected_slot, 1);
+
+    remapper.run_frame(&mut game, &[Keycode::LControl, Keycode::LShift, Keycode::Key3], at(200));
+    assert_eq!(game.selected_slot, 2);
+}
+
+#[test]
+fn select_spell_finds_the_slot_by_param_id() {
+    let started = Instant::now();
+    let mut remapper = remapper_for(
+        "[keybinds]\nselect_spell(swift glintstone shard) = 1\nselect_spell(4030) = 2\nselect_spell(Rock Sling) = 3\n",
+        started,
+    );
+    let mut game = new_game();
+    let at = |milliseconds: u64| started + Duration::from_millis(milliseconds);
+
+    remapper.run_frame(&mut game, &[Keycode::Key1], at(0));
+    assert_eq!(game.selected_slot, 1);
+    remapper.run_frame(&mut game, &[Keycode::Key2], at(100));
+    assert_eq!(game.selected_slot, 3);
+    assert_eq!(
+        remapper.take_messages(),
+        vec!["selected slot 2 (Swift Glintstone Shard)".to_string(), "selected slot 4 (Shard Spiral)".to_string()],
+    );
+
+    remapper.run_frame(&mut game, &[Keycode::Key3], at(200));
+    assert_eq!(game.selected_slot, 3);
+    assert_eq!(remapper.take_messages(), vec!["Rock Sling is not attuned".to_string()]);
+}
+
+#[test]
+fn groups_cycle_through_their_own_slots_and_keep_their_place() {
+    let started = Instant::now();
+    let mut remapper = remapper_for(
+        "[keybinds]\ncycle_forward:odd = 1\ncycle_back:odd = 2\ncycle_forward = 3\n\
+         [settings]\ngroup.odd = 1, 3, 5\n",
+        started,
+    );
+    let mut game = new_game();
+    let at = |milliseconds: u64| started + Duration::from_millis(milliseconds);
+
+    // Slot 5 is empty, so the group wraps from slot 3 back to slot 1
+    remapper.run_frame(&mut game, &[], at(0));
+    remapper.run_frame(&mut game, &[Keycode::Key1], at(50));
+    assert_eq!(game.selected_slot, 2);
+    remapper.run_frame(&mut game, &[Keycode::Key1], at(150));
+    assert_eq!(game.selected_slot, 2);
+    remapper.run_frame(&mut game, &[Keycode::Key1], at(300));
+    assert_eq!(game.selected_slot, 0);
+    remapper.run_frame(&mut game, &[], at(400));
+
+    // Leaving the group picks up from where it was left
+    remapper.run_frame(&mut game, &[Keycode::Key3], at(500));
+    assert_eq!(game.selected_slot, 1);
+    remapper.run_frame(&mut game, &[], at(600));
+    remapper.run_frame(&mut game, &[Keycode::Key2], at(700));
+    assert_eq!(game.selected_slot, 2);
+}
+
+#[test]
+fn swap_to_previous_follows_changes_made_by_the_game_too() {
+    let started = Instant::now();
+    let mut remapper = remapper_for(
+        "[keybinds]\nmemory_slot_3 = 3\nswap_to_previous = Q\n",
+        started,
+    );
+    let mut game = new_game();
+    let at = |milliseconds: u64| started + Duration::from_millis(milliseconds);
+
+    remapper.run_frame(&mut game, &[Keycode::Key3], at(0));
+    remapper.run_frame(&mut game, &[], at(100));
+    remapper.run_frame(&mut game, &[Keycode::Q], at(200));
+    assert_eq!(game.selected_slot, 0);
+    remapper.run_frame(&mut game, &[], at(300));
+    remapper.run_frame(&mut game, &[Keycode::Q], at(400));
+    assert_eq!(game.selected_slot, 2);
+    remapper.run_frame(&mut game, &[], at(500));
+
+    // The game's own cycling counts as a change as well
+    game.set_selected_slot(3);
+    remapper.run_frame(&mut game, &[], at(600));
+    remapper.run_frame(&mut game, &[Keycode::Q], at(700));
+    assert_eq!(game.selected_slot, 2);
+}
+
+#[test]
+fn controller_chords_bind_like_keys() {
+    let started = Instant::now();
+    let mut remapper = remapper_for(
+        "[keybinds]\nmemory_slot_3 = LB+DPadRight\ncycle_forward = DPadRight\nmemory_slot_4 = L2+Shift+4\n",
+        started,
+    );
+    let mut game = new_game();
+    let mut gamepad = FakeGamepad::new();
+    let controller = gamepad.clone();
+    let at = |milliseconds: u64| started + Duration::from_millis(milliseconds);
+    let mut pad_frame = |game: &mut Game, keys: &[Keycode], milliseconds: u64| {
+        let mut pressed = gamepad.poll().unwrap().pressed_keys();
+        pressed.extend_from_slice(keys);
+        remapper.run_frame(game, &pressed, at(milliseconds));
+    };
+
+    // The chord wins over the plain D-pad bind because it uses more buttons
+    controller.press(Keycode::LeftBumper);
+    pad_frame(&mut game, &[], 0);
+    controller.press(Keycode::DPadRight);
+    pad_frame(&mut game, &[], 100);
+    assert_eq!(game.selected_slot, 2);
+    controller.release(Keycode::LeftBumper);
+    controller.release(Keycode::DPadRight);
+    pad_frame(&mut game, &[], 200);
+
+    // Triggers and keyboard modifiers mix in one shortcut
+    controller.press(Keycode::LeftTrigger);
+    pad_frame(&mut game, &[Keycode::LShift, Keycode::Key4], 300);
+    assert_eq!(game.selected_slot, 3);
+}
+
+#[test]
+fn fake_gamepads_start_connected_unless_asked_not_to() {
+    assert_eq!(FakeGamepad::new().poll(), Some(GamepadState::default()));
+    assert_eq!(FakeGamepad::default().poll(), Some(GamepadState::default()));
+
+    let mut gamepad = FakeGamepad::disconnected();
+    assert_eq!(gamepad.poll(), None);
+    gamepad.press(Keycode::PadA);
+    assert_eq!(gamepad.poll().unwrap().pressed_keys(), vec![Keycode::PadA]);
+}
@@ -0,0 +1,114 @@
+use std::path::Path;
+
+use remapper_core::{
+    ini_document::IniDocument,
+    load_config_from_str,
+    schema::{migrate_config_text, migrate_document},
+    DiagnosticKind,
+    CONFIG_VERSION,
+};
+
+const UNVERSIONED: &str = "\
+# My binds
+[keybinds]
+memory_slot_1 = Shift+1 ; the first one
+
+[Other_Settings]
+# How long to wait between cycles
+cycle_debounce_milliseconds = 150
+";
+
+fn migrate(text: &str) -> String {
+    let mut diagnostics = Vec::new();
+    let migrated = migrate_config_text(Path::new("eldenring_remapper.ini"), text, &mut diagnostics);
+    assert!(diagnostics.is_empty());
+    migrated
+}
+
+#[test]
+fn migrated_text_keeps_comments_and_renames_sections() {
+    let migrated = migrate(UNVERSIONED);
+
+    assert_eq!(
+        migrated,
+        format!(
+            "# My binds\n[keybinds]\nmemory_slot_1 = Shift+1 ; the first one\n\n[settings]\nconfig_version = {}\n\
+             # How long to wait between cycles\ncycle_debounce_milliseconds = 150\n",
+            CONFIG_VERSION,
+        ),
+    );
+    // Already current, so running it again changes nothing
+    assert_eq!(migrate(&migrated), migrated);
+}
+
+#[test]
+fn migrated_text_bumps_an_existing_version_or_adds_the_section() {
+    assert_eq!(
+        migrate("[settings]\nconfig_version = 1\nexact_modifiers = true"),
+        format!("[settings]\nconfig_version = {}\nexact_modifiers = true", CONFIG_VERSION),
+    );
+    assert_eq!(
+        migrate("[keybinds]\ncycle_back = 2\n"),
+        format!("[keybinds]\ncycle_back = 2\n\n[settings]\nconfig_version = {}\n", CONFIG_VERSION),
+    );
+}
+
+#[test]
+fn legacy_documents_are_read_under_the_current_names() {
+    let path = Path::new("eldenring_remapper.ini");
+    let mut diagnostics = Vec::new();
+    let mut document = IniDocument::parse(path, UNVERSIONED, &mut diagnostics);
+
+    assert_eq!(migrate_document(path, &mut document, &mut diagnostics), 1);
+    assert_eq!(document.get("settings", "cycle_debounce_milliseconds").map(|entry| entry.value.as_str()), Some("150"));
+    assert!(document.section("other_settings").is_none());
+    assert_eq!(diagnostics.iter().map(|d| (d.kind, d.line)).collect::<Vec<_>>(), vec![(DiagnosticKind::DeprecatedSection, Some(5))]);
+
+    // The migrated text round trips to the same settings without anything left to migrate
+    let migrated = migrate(UNVERSIONED);
+    let mut diagnostics = Vec::new();
+    let mut document = IniDocument::parse(path, &migrated, &mut diagnostics);
+    assert_eq!(migrate_document(path, &mut document, &mut diagnostics), CONFIG_VERSION);
+    assert_eq!(document.get("settings", "cycle_debounce_milliseconds").map(|entry| entry.value.as_str()), Some("150"));
+    assert!(diagnostics.is_empty());
+}
+
+#[test]
+fn migrated_file_loads_the_same_config_and_is_current() {
+    let path = Path::new("eldenring_remapper.ini");
+    let before = load_config_from_str(path, UNVERSIONED);
+    let after = load_config_from_str(path, &migrate(UNVERSIONED));
+
+    assert!(before.is_outdated() && !after.is_outdated());
+    assert_eq!(after.keybinds, before.keybinds);
+    assert_eq!(after.other_settings.cycle_debouce_milliseconds, 150);
+}
+
+#[test]
+fn legacy_entries_are_merged_into_an_existing_section() {
+    assert_eq!(
+        migrate("[settings]\nexact_modifiers = true\n\n[other_settings]\n# Slower\ncycle_debounce_milliseconds = 150\n\n[keybinds]\ncycle_back = 2\n"),
+        format!(
+            "[settings]\nconfig_version = {}\n# Slower\ncycle_debounce_milliseconds = 150\nexact_modifiers = true\n\n[keybinds]\ncycle_back = 2\n",
+            CONFIG_VERSION,
+        ),
+    );
+    // The entries under the current name still win, the same as when the file is loaded
+    let path = Path::new("eldenring_remapper.ini");
+    let text = "[Other_Settings]\ncycle_debounce_milliseconds = 150\n\n[settings]\ncycle_debounce_milliseconds = 300\n";
+    let before = load_config_from_str(path, text);
+    let after = load_config_from_str(path, &migrate(text));
+    assert_eq!(before.other_settings.cycle_debouce_milliseconds, 300);
+    assert_eq!(after.other_settings.cycle_debouce_milliseconds, 300);
+    assert_eq!(migrate(text).matches("[settings]").count(), 1);
+}
+
+#[test]
+fn newer_versions_are_left_alone() {
+    let text = format!("[other_settings]\nconfig_version = {}\n", CONFIG_VERSION + 1);
+    let mut diagnostics = Vec::new();
+
+    assert_eq!(migrate_config_text(Path::new("eldenring_remapper.ini"), &text, &mut diagnostics), text);
+    assert_eq!(diagnostics.iter().map(|d| (d.kind, d.line)).collect::<Vec<_>>(), vec![(DiagnosticKind::UnsupportedVersion, Some(2))]);
+    assert!(diagnostics[0].reason.ends_with("leaving the file as it is"));
+}
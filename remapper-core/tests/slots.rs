@@ -0,0 +1,94 @@
+use remapper_core::{
+    slots::{back_cycle_memory_slot, forward_cycle_memory_slot, select_spell, set_memory_slot},
+    MagicSlots, SlotBoundary,
+};
+
+struct Magic {
+    param_ids: Vec<i32>,
+    selected_slot: i32,
+}
+
+impl MagicSlots for Magic {
+    fn param_ids(&self) -> Vec<i32> {
+        self.param_ids.clone()
+    }
+
+    fn selected_slot(&self) -> i32 {
+        self.selected_slot
+    }
+
+    fn set_selected_slot(&mut self, slot: i32) {
+        self.selected_slot = slot;
+    }
+}
+
+#[test]
+fn cycling_and_setting_skip_empty_slots() {
+    // Only the even slots are attuned
+    let mut magic = Magic { param_ids: vec![4000, -1, 4010, -1, 4020, -1], selected_slot: 0 };
+
+    forward_cycle_memory_slot(&mut magic, SlotBoundary::Wrap);
+    assert_eq!(magic.selected_slot, 2);
+    forward_cycle_memory_slot(&mut magic, SlotBoundary::Wrap);
+    forward_cycle_memory_slot(&mut magic, SlotBoundary::Wrap);
+    assert_eq!(magic.selected_slot, 0);
+    back_cycle_memory_slot(&mut magic, SlotBoundary::Wrap);
+    assert_eq!(magic.selected_slot, 4);
+    back_cycle_memory_slot(&mut magic, SlotBoundary::Wrap);
+    assert_eq!(magic.selected_slot, 2);
+
+    set_memory_slot(&mut magic, 4, SlotBoundary::Clamp, false);
+    assert_eq!(magic.selected_slot, 4);
+    set_memory_slot(&mut magic, 3, SlotBoundary::Clamp, false);
+    assert_eq!(magic.selected_slot, 2);
+    set_memory_slot(&mut magic, 11, SlotBoundary::Clamp, false);
+    assert_eq!(magic.selected_slot, 4);
+}
+
+#[test]
+fn nothing_attuned_leaves_the_selection_alone() {
+    let mut magic = Magic { param_ids: vec![-1; 12], selected_slot: 0 };
+
+    forward_cycle_memory_slot(&mut magic, SlotBoundary::Wrap);
+    back_cycle_memory_slot(&mut magic, SlotBoundary::Wrap);
+    set_memory_slot(&mut magic, 3, SlotBoundary::Clamp, false);
+    assert_eq!(magic.selected_slot, 0);
+
+    magic.param_ids.clear();
+    forward_cycle_memory_slot(&mut magic, SlotBoundary::Wrap);
+    set_memory_slot(&mut magic, 3, SlotBoundary::Clamp, false);
+    assert_eq!(magic.selected_slot, 0);
+}
+
+#[test]
+fn slot_boundaries_wrap_clamp_or_ignore() {
+    let mut magic = Magic { param_ids: vec![4000, 4010, -1, 4020, -1, -1], selected_slot: 3 };
+
+    assert!(forward_cycle_memory_slot(&mut magic, SlotBoundary::Clamp));
+    assert_eq!(magic.selected_slot, 3);
+    assert!(!forward_cycle_memory_slot(&mut magic, SlotBoundary::Ignore));
+    assert_eq!(magic.selected_slot, 3);
+
+    magic.selected_slot = 0;
+    assert!(back_cycle_memory_slot(&mut magic, SlotBoundary::Clamp));
+    assert_eq!(magic.selected_slot, 0);
+    assert!(!back_cycle_memory_slot(&mut magic, SlotBoundary::Ignore));
+
+    // Three spells, so the fifth slot wraps round to the second spell
+    assert!(set_memory_slot(&mut magic, 4, SlotBoundary::Wrap, false));
+    assert_eq!(magic.selected_slot, 1);
+    assert!(!set_memory_slot(&mut magic, 4, SlotBoundary::Ignore, false));
+    assert!(!set_memory_slot(&mut magic, 2, SlotBoundary::Clamp, true));
+    assert_eq!(magic.selected_slot, 1);
+}
+
+#[test]
+fn select_spell_never_picks_an_empty_slot() {
+    let mut magic = Magic { param_ids: vec![4000, -1, 0, 4010], selected_slot: 3 };
+
+    assert_eq!(select_spell(&mut magic, -1), None);
+    assert_eq!(select_spell(&mut magic, 0), None);
+    assert_eq!(magic.selected_slot, 3);
+    assert_eq!(select_spell(&mut magic, 4000), Some(0));
+    assert_eq!(magic.selected_slot, 0);
+}
@@ -0,0 +1,50 @@
+use std::{collections::HashSet, path::Path};
+
+use remapper_core::{load_config_from_str, spells::parse_spell, spell_by_name, spell_by_param_id, spells, Action, DiagnosticKind, SpellType};
+
+#[test]
+fn spells_are_found_by_param_id_and_loosely_written_name() {
+    let comet_azur = spell_by_param_id(4140).unwrap();
+    assert_eq!((comet_azur.name, comet_azur.spell_type), ("Comet Azur", SpellType::Sorcery));
+
+    assert_eq!(spell_by_name("lorettas greatbow").map(|spell| spell.param_id), Some(4380));
+    assert_eq!(spell_by_name("O, Flame!").map(|spell| spell.spell_type), Some(SpellType::Incantation));
+    assert_eq!(spell_by_name("Comet Azure"), None);
+    assert_eq!(spell_by_param_id(1), None);
+}
+
+#[test]
+fn shipped_table_keeps_every_entered_spell() {
+    // The table is still partial, raise these when spells are added from a Magic param dump
+    let count = |spell_type| spells().iter().filter(|spell| spell.spell_type == spell_type).count();
+    assert_eq!((count(SpellType::Sorcery), count(SpellType::Incantation)), (68, 93));
+
+    let known = [(4000, "Glintstone Pebble"), (4380, "Loretta's Greatbow"), (4910, "Great Oracular Bubble"), (6000, "Catch Flame"), (7300, "Pest Threads")];
+    for (param_id, name) in known {
+        assert_eq!(spell_by_param_id(param_id).map(|spell| spell.name), Some(name));
+    }
+    assert!(spells().windows(2).all(|pair| pair[0].param_id < pair[1].param_id));
+}
+
+#[test]
+fn spell_names_and_param_ids_are_unique() {
+    let mut param_ids = HashSet::new();
+    let mut names = HashSet::new();
+    for spell in spells() {
+        assert!(param_ids.insert(spell.param_id), "param ID {} is in the table twice", spell.param_id);
+        assert!(names.insert(spell.name.to_lowercase()), "{} is in the table twice", spell.name);
+    }
+}
+
+#[test]
+fn empty_slot_ids_are_not_spells() {
+    assert_eq!(parse_spell("4140"), Ok(4140));
+    assert!(parse_spell("-1").is_err());
+    assert!(parse_spell("0").is_err());
+
+    let config = load_config_from_str(Path::new("eldenring_remapper.ini"), "[keybinds]\nselect_spell(-1) = 7\n");
+
+    let diagnostic = config.diagnostics.iter().find(|d| d.kind == DiagnosticKind::UnknownSpell).unwrap();
+    assert_eq!(diagnostic.key.as_deref(), Some("select_spell(-1)"));
+    assert!(config.keybinds.iter().all(|keybind| !matches!(keybind.action, Action::SelectSpell(_))));
+}
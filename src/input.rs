@@ -0,0 +1,83 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use device_query::{DeviceQuery, DeviceState};
+use remapper_core::{
+    mapper::{map_virtual_key, reported_keycodes, virtual_key_code},
+    InputFrame,
+    InputSource,
+    Keycode,
+};
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetAsyncKeyState, MapVirtualKeyW, MAPVK_VK_TO_VSC_EX};
+
+use crate::mapper::{is_missing_from_device_query, map_device_keycode};
+
+// Only used when the input hooks can't be installed, a tap that starts and ends between two frames is missed
+pub struct DeviceQueryInputSource {
+    device_state: DeviceState,
+    // Shared with the reload code so the physical_keys setting applies without a restart
+    physical_keys: Arc<AtomicBool>,
+}
+
+impl DeviceQueryInputSource {
+    pub fn new(physical_keys: Arc<AtomicBool>) -> Self {
+        DeviceQueryInputSource {
+            device_state: DeviceState::new(),
+            physical_keys,
+        }
+    }
+}
+
+fn is_down(code: u8) -> bool {
+    unsafe { GetAsyncKeyState(code as i32) as u16 & 0x8000 != 0 }
+}
+
+// 0xE0 prefixed in the high byte for extended keys, the same as Keycode::ScanCode. None for mouse buttons
+pub fn virtual_key_to_scan_code(code: u8) -> Option<u16> {
+    let scan_code = unsafe { MapVirtualKeyW(code as u32, MAPVK_VK_TO_VSC_EX) };
+    (scan_code != 0).then_some(scan_code as u16)
+}
+
+// The name, plus the position and scan code the same key is also bound by
+fn report(keycode: Keycode, physical_keys: bool, pressed_keys: &mut Vec<Keycode>) {
+    match virtual_key_code(keycode) {
+        Some(code) => pressed_keys.extend(reported_keycodes(code, virtual_key_to_scan_code(code), physical_keys)),
+        None => pressed_keys.push(keycode),
+    }
+}
+
+impl InputSource for DeviceQueryInputSource {
+    fn poll(&mut self) -> Option<InputFrame> {
+        let physical_keys = self.physical_keys.load(Ordering::Relaxed);
+        let mut pressed_keys = Vec::new();
+        for keycode in self.device_state.get_keys().iter().filter_map(map_device_keycode) {
+            report(keycode, physical_keys, &mut pressed_keys);
+        }
+
+        // device_query numbers the buttons from 1, 4 and 5 are the side buttons
+        let mouse = self.device_state.get_mouse();
+        for (button, keycode) in [(4, Keycode::Mouse4), (5, Keycode::Mouse5)] {
+            if mouse.button_pressed.get(button).copied().unwrap_or(false) {
+                pressed_keys.push(keycode);
+            }
+        }
+
+        // F21 to F24, media keys, the ISO key and anything bound as VK_0x..
+        for code in 0..=u8::MAX {
+            let missing = map_virtual_key(code).is_some_and(is_missing_from_device_query);
+            if missing && is_down(code) {
+                pressed_keys.extend(reported_keycodes(code, virtual_key_to_scan_code(code), physical_keys));
+            }
+        }
+
+        Some(InputFrame {
+            timestamp: Instant::now(),
+            pressed_keys,
+        })
+    }
+}
@@ -0,0 +1,137 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicI32, Ordering},
+        mpsc::{channel, Sender},
+        Arc,
+        OnceLock,
+    },
+    time::Instant,
+};
+
+use remapper_core::{mapper::reported_keycodes, KeyEvent, KeyEventHook, WheelNotches};
+use windows::Win32::{
+    Foundation::{LPARAM, LRESULT, WPARAM},
+    UI::WindowsAndMessaging::{
+        CallNextHookEx,
+        GetMessageW,
+        SetWindowsHookExW,
+        UnhookWindowsHookEx,
+        KBDLLHOOKSTRUCT,
+        LLKHF_EXTENDED,
+        MSG,
+        MSLLHOOKSTRUCT,
+        WHEEL_DELTA,
+        WH_KEYBOARD_LL,
+        WH_MOUSE_LL,
+        WM_KEYDOWN,
+        WM_KEYUP,
+        WM_MOUSEWHEEL,
+        WM_SYSKEYDOWN,
+        WM_SYSKEYUP,
+        WM_XBUTTONDOWN,
+        WM_XBUTTONUP,
+    },
+};
+
+const VK_XBUTTON1: u8 = 0x05;
+const VK_XBUTTON2: u8 = 0x06;
+const VK_RSHIFT: u32 = 0xA1;
+
+// What the hook callbacks need, they are plain functions so it has to live in a static
+struct HookState {
+    events: Sender<KeyEvent>,
+    wheel_notches: WheelNotches,
+    physical_keys: Arc<AtomicBool>,
+}
+
+static STATE: OnceLock<HookState> = OnceLock::new();
+// Scrolling short of a whole notch, high resolution wheels report a fraction of WHEEL_DELTA at a time
+static PARTIAL_DELTA: AtomicI32 = AtomicI32::new(0);
+
+// Low level keyboard and mouse hooks, so presses are seen when they happen instead of once a frame.
+// The mouse one also counts wheel notches, which polling can't see at all
+pub struct LowLevelHook {
+    pub wheel_notches: WheelNotches,
+    pub physical_keys: Arc<AtomicBool>,
+}
+
+impl KeyEventHook for LowLevelHook {
+    fn start(self, events: Sender<KeyEvent>) -> Result<(), String> {
+        let state = HookState { events, wheel_notches: self.wheel_notches, physical_keys: self.physical_keys };
+        STATE.set(state).map_err(|_| "the input hooks are already running".to_string())?;
+
+        // A low level hook runs on the thread that installed it, so they get a thread of their own with a message loop
+        let (installed, is_installed) = channel();
+        std::thread::spawn(move || unsafe {
+            let keyboard = SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_hook), None, 0);
+            let mouse = SetWindowsHookExW(WH_MOUSE_LL, Some(mouse_hook), None, 0);
+            let result = match (&keyboard, &mouse) {
+                (Ok(_), Ok(_)) => Ok(()),
+                (Err(e), _) | (_, Err(e)) => Err(e.to_string()),
+            };
+            // Half the hooks would drop events without anyone noticing, the polling fallback is better
+            if result.is_err() {
+                for hook in [keyboard, mouse].into_iter().flatten() {
+                    let _ = UnhookWindowsHookEx(hook);
+                }
+                let _ = installed.send(result);
+                return;
+            }
+            let _ = installed.send(result);
+            let mut message = MSG::default();
+            while GetMessageW(&mut message, None, 0, 0).as_bool() {}
+        });
+        is_installed.recv().map_err(|e| e.to_string())?
+    }
+}
+
+fn send(virtual_key: u8, scan_code: Option<u16>, is_down: bool) {
+    let Some(state) = STATE.get() else {
+        return;
+    };
+    let timestamp = Instant::now();
+    for keycode in reported_keycodes(virtual_key, scan_code, state.physical_keys.load(Ordering::Relaxed)) {
+        // Only fails once the game thread's end is gone, and then nobody is listening anyway
+        let _ = state.events.send(KeyEvent { keycode, is_down, timestamp });
+    }
+}
+
+unsafe extern "system" fn keyboard_hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    let message = wparam.0 as u32;
+    if code >= 0 && matches!(message, WM_KEYDOWN | WM_SYSKEYDOWN | WM_KEYUP | WM_SYSKEYUP) {
+        let info = unsafe { &*(lparam.0 as *const KBDLLHOOKSTRUCT) };
+        // Right Shift comes flagged as extended but isn't, MapVirtualKey and the polling source say 0x36
+        let is_extended = info.flags.0 & LLKHF_EXTENDED.0 != 0 && info.vkCode != VK_RSHIFT;
+        let scan_code = (info.scanCode != 0).then_some(info.scanCode as u16 | if is_extended { 0xE000 } else { 0 });
+        send(info.vkCode as u8, scan_code, matches!(message, WM_KEYDOWN | WM_SYSKEYDOWN));
+    }
+    unsafe { CallNextHookEx(None, code, wparam, lparam) }
+}
+
+unsafe extern "system" fn mouse_hook(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    let message = wparam.0 as u32;
+    if code >= 0 && matches!(message, WM_MOUSEWHEEL | WM_XBUTTONDOWN | WM_XBUTTONUP) {
+        let info = unsafe { &*(lparam.0 as *const MSLLHOOKSTRUCT) };
+        // The high word of mouseData is the signed scroll distance, or which side button it was
+        let high_word = (info.mouseData >> 16) as u16;
+        match message {
+            WM_MOUSEWHEEL => add_wheel_delta(high_word as i16 as i32),
+            _ => {
+                let virtual_key = if high_word == 1 { VK_XBUTTON1 } else { VK_XBUTTON2 };
+                send(virtual_key, None, message == WM_XBUTTONDOWN);
+            }
+        }
+    }
+    unsafe { CallNextHookEx(None, code, wparam, lparam) }
+}
+
+fn add_wheel_delta(delta: i32) {
+    let total = PARTIAL_DELTA.fetch_add(delta, Ordering::Relaxed) + delta;
+    let notches = total / WHEEL_DELTA as i32;
+    if notches != 0 {
+        PARTIAL_DELTA.fetch_sub(notches * WHEEL_DELTA as i32, Ordering::Relaxed);
+        if let Some(state) = STATE.get() {
+            state.wheel_notches.add(notches);
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use remapper_core::{
+    mapper::{scan_code, virtual_key_code},
+    Keycode,
+};
+use windows::Win32::UI::Input::KeyboardAndMouse::GetKeyNameTextW;
+
+use crate::input::virtual_key_to_scan_code;
+
+// What the current keyboard layout prints on the key a bind reacts to, e.g. "A" for Q when physical_keys is
+// on and the layout is AZERTY. Only keys that type a character have one, the rest are named the same everywhere
+pub fn key_label(keycode: Keycode, physical_keys: bool) -> Option<String> {
+    let scan_code = match keycode {
+        Keycode::ScanCode(code) => code,
+        _ if physical_keys => scan_code(keycode)?,
+        _ => virtual_key_to_scan_code(virtual_key_code(keycode)?)?,
+    };
+    // GetKeyNameText takes the scan code in bits 16 to 23 and the extended flag in bit 24
+    let lparam = ((scan_code as i32 & 0xFF) << 16) | if scan_code >> 8 == 0xE0 { 1 << 24 } else { 0 };
+    let mut buffer = [0u16; 64];
+    let length = unsafe { GetKeyNameTextW(lparam, &mut buffer) };
+    let label = String::from_utf16_lossy(&buffer[..length.max(0) as usize]);
+    (label.chars().count() == 1).then_some(label)
+}
@@ -1,9 +1,10 @@
+mod input;
+mod input_hook;
+mod layout;
 mod mapper;
-mod simple_logger;
+mod xinput;
 
-use crate::{mapper::{map_key, map_modifier}, simple_logger::SimpleLogger};
-
-use ini::ini;
+use crate::{input::DeviceQueryInputSource, input_hook::LowLevelHook, layout::key_label, xinput::XInputGamepad};
 
 use pelite::{
     pattern,
@@ -11,9 +12,13 @@ use pelite::{
     pe64::{Pe, PeObject, PeView},
 };
 use std::{
-    collections::{HashMap, HashSet},
+    fs::File,
     path::Path,
     ptr::read_unaligned,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
 use windows::{
@@ -37,27 +42,30 @@ use eldenring::{
 
 use fromsoftware_shared::{program::Program, task::*, FromStatic};
 
-use device_query::{DeviceQuery, DeviceState, Keycode};
-use keyboard_codes::{parse_input, Modifier, Shortcut};
+use remapper_core::{
+    build_keybindings,
+    build_layers,
+    describe_config_changes,
+    load_config,
+    ConfigWatcher,
+    EventInputSource,
+    GamepadInputSource,
+    GameState,
+    InputSource,
+    LoadedConfig,
+    MagicSlots,
+    RecordingInputSource,
+    Remapper,
+    SimpleLogger,
+    WheelInputSource,
+    WheelNotches,
+    CONFIG_VERSION,
+};
 
 const GAME_DATA_MAN_PATTERN_STR: &str = "48 8B 05 ? ? ? ? 48 85 C0 74 05 48 8B 40 58 C3 C3";
 
 const OFFSET: usize = 3;
 const ADDITIONAL: usize = 7;
-const DEFAULT_CYCLE_DEBOUNCE_MILLISECONDS: u64 = 200;
-
-#[derive(Clone, Copy)]
-enum Action {
-    SetMemorySlot(u8),
-    CycleBack,
-    CycleForward,
-    NoOp,
-}
-
-#[derive(Clone)]
-pub struct OtherSettings {
-    pub cycle_debouce_milliseconds: u64
-}
 
 fn get_pe_view() -> PeView<'static> {
     let pe_view = match Program::current() {
@@ -142,163 +150,89 @@ fn get_dll_path() -> String {
     }
 }
 
-fn config_key_to_action(key: &String) -> Action {
-    match key.strip_prefix("memory_slot_") {
-        Some(s) => {
-            let slot: u8 = s.parse().unwrap();
-            Action::SetMemorySlot(slot)
-        }
-        None => {
-            if key.contains("cycle_back") {
-                return Action::CycleBack;
-            } else if key.contains("cycle_forward") {
-                return Action::CycleForward;
-            }
-            Action::NoOp
-        }
-    }
+struct EldenRingGame<'a> {
+    game_data_man: &'a mut GameDataMan,
+    player_hp: i32,
 }
 
-fn read_keybinds_config(config: &HashMap<String, HashMap<String, Option<String>>>) -> HashMap<Shortcut, Action> {
-    let config: HashMap<Shortcut, Action> = config["keybinds"].iter()
-        .map(|(k, v)| { (k, parse_input(&v.clone().unwrap_or(String::new()))) })
-        .filter(|kv| kv.1.is_ok())
-        .map(|(k, v)| (k, v.unwrap()))
-        .map(|(k, v)| { (config_key_to_action(k), v) })
-        .filter(|(action, _)| !matches!(action, Action::NoOp))
-        .map(|(k, v)| (v, k))
-        .collect();
-
-    config
-}
+impl MagicSlots for EldenRingGame<'_> {
+    fn param_ids(&self) -> Vec<i32> {
+        let equipped_magic_ptr = self.game_data_man.main_player_game_data.equipment.equip_magic_data.as_ptr();
+        let equipped_magic = unsafe { &*equipped_magic_ptr };
 
-fn read_other_settings_config(config: &HashMap<String, HashMap<String, Option<String>>>) -> OtherSettings {
-    let Some(settings_map) = config.get("settings") else {
-        return OtherSettings { cycle_debouce_milliseconds: DEFAULT_CYCLE_DEBOUNCE_MILLISECONDS };
-    };
-    OtherSettings {
-        cycle_debouce_milliseconds: settings_map.get("cycle_debounce_milliseconds")
-                                                .and_then(|v| v.as_deref())
-                                                .and_then(|s| s.parse::<u64>().ok())
-                                                .unwrap_or(DEFAULT_CYCLE_DEBOUNCE_MILLISECONDS)
+        equipped_magic.entries.iter()
+            .map(|e| e.param_id)
+            .collect()
     }
-}
-
-fn set_memory_slot(game_data_man: &mut GameDataMan, slot_index: u8) {
-    let equipped_magic_ptr = game_data_man.main_player_game_data.equipment.equip_magic_data.as_ptr();
-    let equipped_magic = unsafe { &mut *equipped_magic_ptr };
 
-    let last_slot = equipped_magic.entries.iter()
-        .filter(|e| e.param_id > 1)
-        .count() - 1;
+    fn selected_slot(&self) -> i32 {
+        let equipped_magic_ptr = self.game_data_man.main_player_game_data.equipment.equip_magic_data.as_ptr();
+        let equipped_magic = unsafe { &*equipped_magic_ptr };
 
-    if slot_index > last_slot as u8 {
-        equipped_magic.selected_slot = last_slot as i32;
-        return;
+        equipped_magic.selected_slot
     }
 
-    equipped_magic.selected_slot = slot_index as i32;
-}
+    fn set_selected_slot(&mut self, slot: i32) {
+        let equipped_magic_ptr = self.game_data_man.main_player_game_data.equipment.equip_magic_data.as_ptr();
+        let equipped_magic = unsafe { &mut *equipped_magic_ptr };
 
-fn forward_cycle_memory_slot(game_data_man: &mut GameDataMan) {
-    let equipped_magic_ptr = game_data_man.main_player_game_data.equipment.equip_magic_data.as_ptr();
-    let equipped_magic = unsafe { &mut *equipped_magic_ptr };
-
-    let last_slot: i32 = (equipped_magic.entries.iter()
-        .filter(|e| e.param_id > 1)
-        .count() - 1)
-        .try_into()
-        .unwrap();
-
-    let next_slot = equipped_magic.selected_slot + 1;
-
-    if next_slot > last_slot {
-        equipped_magic.selected_slot = 0 as i32;
-        return;
+        equipped_magic.selected_slot = slot;
     }
-
-    equipped_magic.selected_slot = next_slot;
 }
 
-fn back_cycle_memory_slot(game_data_man: &mut GameDataMan) {
-    let equipped_magic_ptr = game_data_man.main_player_game_data.equipment.equip_magic_data.as_ptr();
-    let equipped_magic = unsafe { &mut *equipped_magic_ptr };
+impl GameState for EldenRingGame<'_> {
+    type HudType = HudType;
 
-    let previous_slot = equipped_magic.selected_slot - 1;
+    const HUD_ON: HudType = HudType::On;
 
-    if previous_slot < 0 {
-        let last_slot = equipped_magic.entries.iter()
-            .filter(|e| e.param_id > 1)
-            .count() - 1;
-        equipped_magic.selected_slot = last_slot as i32;
-        return;
+    fn player_hp(&self) -> i32 {
+        self.player_hp
     }
 
-    equipped_magic.selected_slot = previous_slot;
-}
-
-fn cartesian_product(keycodes: Vec<HashSet<Keycode>>) -> Vec<HashSet<Keycode>> {
-    if keycodes.is_empty() {
-        return vec![HashSet::new()];
+    fn hud_type(&self) -> HudType {
+        self.game_data_man.game_settings.hud_type
     }
-    let set = &keycodes[0];
-    let cartesian = cartesian_product(keycodes[1..].to_vec());
-
-    let mut product = Vec::new();
 
-    for keycode in set {
-        for set in &cartesian {
-            let mut current_set = set.clone();
-            current_set.insert(*keycode);
-            product.push(current_set);
-        }
+    fn set_hud_type(&mut self, hud_type: HudType) {
+        self.game_data_man.game_settings.hud_type = hud_type;
     }
-
-    product
 }
 
-fn expand_combinations(key: Keycode, modifiers: Vec<Modifier>, action: Action) -> Vec<(HashSet<Keycode>, Action)> {
-    if modifiers.is_empty() {
-        let mut set = HashSet::new();
-        set.insert(key);
-        return vec![(set, action)];
+fn log_config(config: &LoadedConfig, config_path: &Path, logger: &SimpleLogger) {
+    for diagnostic in &config.diagnostics {
+        if diagnostic.kind.is_warning() {
+            logger.log_warning(&diagnostic.to_string());
+        } else {
+            logger.log_info(&diagnostic.to_string());
+        }
+    }
+    // Left to `cargo migrate-config`, rewriting the file from here would retrigger the reload
+    // and overwrite the backup every time
+    if config.is_outdated() {
+        logger.log_warning(&format!(
+            "{} is config version {}, still read as is but run `cargo migrate-config {}` to update it to version {}",
+            config_path.display(), config.config_version, config_path.display(), CONFIG_VERSION
+        ));
+    }
+    // Every bind with what its keys are labelled on this layout, which differs for e.g. letter binds on AZERTY
+    let physical_keys = config.other_settings.physical_keys;
+    for keybind in &config.keybinds {
+        let labelled = keybind.format_steps(|keycode| key_label(keycode, physical_keys));
+        logger.log_info(&format!("{} is {} on this keyboard layout", keybind.describe(), labelled));
     }
-    let mod_keycodes = modifiers.iter()
-        .map(|m| {
-            let mut set = HashSet::new();
-
-            match map_modifier(m) {
-                (base_modifier, None) => {
-                    set.insert(base_modifier);
-                }
-                (base_modifier, Some(add_modifier)) => {
-                    set.insert(base_modifier);
-                    set.insert(add_modifier);
-                }
-            }
-            set
-        })
-        .collect::<Vec<HashSet<Keycode>>>();
-
-    let mut key_keycode = HashSet::new();
-    key_keycode.insert(key);
-
-    let mut joined_keycodes = mod_keycodes.clone();
-    joined_keycodes.push(key_keycode);
-
-    cartesian_product(joined_keycodes)
-        .into_iter()
-        .map(|p| (p, action))
-        .collect()
 }
 
-fn is_all_keybinding_keys_pressed(keybindings: &HashSet<Keycode>, pressed_keys: &Vec<Keycode>) -> bool {
-    for key in keybindings {
-        if !pressed_keys.contains(key) {
-            return false;
+// Keyboard and mouse buttons, the wheel and the controller, everything binds can be written with
+fn live_input_source(physical_keys: Arc<AtomicBool>, logger: &SimpleLogger) -> Box<dyn InputSource + Send> {
+    let wheel_notches = WheelNotches::new();
+    let hook = LowLevelHook { wheel_notches: wheel_notches.clone(), physical_keys: physical_keys.clone() };
+    match EventInputSource::start(hook) {
+        Ok(keys) => Box::new(GamepadInputSource::new(WheelInputSource::new(keys, wheel_notches), XInputGamepad::new())),
+        Err(e) => {
+            logger.log_warning(&format!("Failed to install the input hooks, checking keys once a frame instead: {}", e));
+            Box::new(GamepadInputSource::new(DeviceQueryInputSource::new(physical_keys), XInputGamepad::new()))
         }
     }
-    true
 }
 
 #[unsafe(no_mangle)]
@@ -311,41 +245,66 @@ pub unsafe extern "C" fn DllMain(_hmodule: u64, reason: u32) -> bool {
         wait_for_system_init(&Program::current(), Duration::MAX)
             .expect("Timeout waiting for system init");
 
-        let device_state = DeviceState::new();
-
         let dll_path = get_dll_path();
         let log_path = Path::new(&dll_path).join(Path::new("eldenring_remapper.log"));
         let logger = SimpleLogger::new(&log_path);
         logger.log_debug("In remapper thread");
-        let config = ini!(&(dll_path + "\\eldenring_remapper.ini"));
-        let other_settings_config = read_other_settings_config(&config);
-        let keybinds_config = read_keybinds_config(&config);
-
-        let mut last_cycle_back_run = Instant::now();
-        let mut last_cycle_forward_run = Instant::now();
-        let cycle_rebound = Duration::from_millis(other_settings_config.cycle_debouce_milliseconds);
-
-        let mut last_hud_update_run = Instant::now();
-        let hud_update_rebound = Duration::from_secs(3);
-        let mut player_hud_type = None;
-        let mut is_hud_restored = true;
+        let config_path = Path::new(&dll_path).join(Path::new("eldenring_remapper.ini"));
+        let config = load_config(&config_path);
+        log_config(&config, &config_path, &logger);
+        let mut other_settings_config = config.other_settings;
+        let mut keybinds_config = config.keybinds;
+        let mut layers_config = config.layers;
+        let mut config_watcher = ConfigWatcher::new(&config_path, Instant::now());
+
+        let physical_keys = Arc::new(AtomicBool::new(other_settings_config.physical_keys));
+        let mut input_source = live_input_source(physical_keys.clone(), &logger);
+        if other_settings_config.record_input {
+            let recording_path = Path::new(&dll_path).join(Path::new("eldenring_remapper_input.rec"));
+            // The hooks only start once, so the file is opened first to keep the source on failure
+            match File::create(&recording_path) {
+                Ok(file) => {
+                    logger.log_info(&format!("Recording input to {}", recording_path.display()));
+                    input_source = Box::new(RecordingInputSource::new(input_source, file));
+                }
+                Err(e) => logger.log_info(&format!("Failed to start input recording: {}", e)),
+            }
+        }
 
         let cs_task = unsafe { CSTaskImp::instance().unwrap() };
 
-        let mut keybindings = keybinds_config.iter()
-                    .map(|(s, a)| (s.key, s.modifiers.clone(), a.clone()))
-                    .map(|(k, m, a)| (map_key(&k), m, a))
-                    .filter(|kma| kma.0.is_some())
-                    .map(|(k, m, a)| (k.unwrap(), m, a))
-                    .flat_map(|(k, m, a)| expand_combinations(k, m, a))
-                    .collect::<Vec<(HashSet<Keycode>, Action)>>();
-
-        keybindings.sort_by(|(x, _), (y, _)| y.len().cmp(&x.len()));
+        let keybindings = build_keybindings(&keybinds_config);
+        let mut remapper = Remapper::new(keybindings, build_layers(&layers_config), &other_settings_config, Instant::now());
 
         logger.log_debug("We've finished setup");
 
         cs_task.run_recurring(
             move |_: &FD4TaskData| {
+                if let Some(config) = config_watcher.poll(Instant::now()) {
+                    logger.log_info("Config changed, reloading");
+                    log_config(&config, &config_path, &logger);
+                    let changes = describe_config_changes(
+                        &keybinds_config,
+                        &layers_config,
+                        &other_settings_config,
+                        &config.keybinds,
+                        &config.layers,
+                        &config.other_settings,
+                    );
+                    for change in &changes {
+                        logger.log_info(change);
+                    }
+                    remapper.reload(build_keybindings(&config.keybinds), build_layers(&config.layers), &config.other_settings);
+                    physical_keys.store(config.other_settings.physical_keys, Ordering::Relaxed);
+                    keybinds_config = config.keybinds;
+                    layers_config = config.layers;
+                    other_settings_config = config.other_settings;
+                }
+
+                // Taken every frame, so presses made on a loading screen are dropped instead of piling up
+                // and all firing on the first frame in game
+                let changes = input_source.poll_changes();
+
                 let Some(main_player) = unsafe { WorldChrMan::instance() }
                     .ok()
                     .and_then(|wcm| wcm.main_player.as_mut())
@@ -353,73 +312,16 @@ pub unsafe extern "C" fn DllMain(_hmodule: u64, reason: u32) -> bool {
                     return
                 };
 
-                let game_data_man = get_game_data_man();
-
-                if main_player.chr_ins.module_container.data.hp <= 0 {
-                    return;
-                }
-
-                if player_hud_type.is_none() {
-                    player_hud_type = Some(game_data_man.game_settings.hud_type);
-                }
-
-                let is_need_to_restore_hud = !is_hud_restored &&
-                    last_hud_update_run.elapsed() > hud_update_rebound &&
-                    game_data_man.game_settings.hud_type != player_hud_type.unwrap();
+                let mut game = EldenRingGame {
+                    game_data_man: get_game_data_man(),
+                    player_hp: main_player.chr_ins.module_container.data.hp,
+                };
 
-                if is_need_to_restore_hud {
-                    game_data_man.game_settings.hud_type = player_hud_type.unwrap();
-                    is_hud_restored = true;
+                for input in &changes {
+                    remapper.run_frame(&mut game, &input.pressed_keys, input.timestamp);
                 }
-
-                let pressed_keys = device_state.get_keys();
-                let mut is_cycling = false;
-
-                for (keybinds, action) in &keybindings {
-                    if !is_all_keybinding_keys_pressed(&keybinds, &pressed_keys) {
-                        continue;
-                    }
-                    match action {
-                        Action::SetMemorySlot(slot) => {
-                            game_data_man.game_settings.hud_type = HudType::On;
-                            last_hud_update_run = Instant::now();
-                            is_hud_restored = false;
-
-                            set_memory_slot(game_data_man, slot - 1);
-                        }
-                        Action::CycleBack => {
-                            is_cycling = true;
-                            if last_cycle_back_run.elapsed() < cycle_rebound {
-                                return;
-                            }
-                            game_data_man.game_settings.hud_type = HudType::On;
-                            last_hud_update_run = Instant::now();
-                            is_hud_restored = false;
-
-                            back_cycle_memory_slot(game_data_man);
-                            last_cycle_back_run = Instant::now();
-                        }
-                        Action::CycleForward => {
-                            is_cycling = true;
-                            if last_cycle_forward_run.elapsed() < cycle_rebound {
-                                return;
-                            }
-                            game_data_man.game_settings.hud_type = HudType::On;
-                            last_hud_update_run = Instant::now();
-                            is_hud_restored = false;
-
-                            forward_cycle_memory_slot(game_data_man);
-                            last_cycle_forward_run = Instant::now();
-                        }
-                        Action::NoOp => { }
-                    }
-                    break;
-                }
-
-                if !is_cycling {
-                    // When no keys are pressed, it's safe to reset the debounce so the player can press the buttons rapidly
-                    last_cycle_forward_run = Instant::now() - cycle_rebound;
-                    last_cycle_back_run = Instant::now() - cycle_rebound;
+                for message in remapper.take_messages() {
+                    logger.log_info(&message);
                 }
             },
             CSTaskGroupIndex::FrameBegin,
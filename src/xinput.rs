@@ -0,0 +1,59 @@
+use std::time::{Duration, Instant};
+
+use remapper_core::{GamepadBackend, GamepadState};
+use windows::Win32::{
+    Foundation::ERROR_SUCCESS,
+    UI::Input::XboxController::{XInputGetState, XINPUT_STATE, XUSER_MAX_COUNT},
+};
+
+// Asking XInput about an empty slot is slow enough to cost frames, so a missing controller is only
+// looked for again after this long
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+
+// Reads the first connected XInput controller
+pub struct XInputGamepad {
+    user_index: Option<u32>,
+    last_search: Option<Instant>,
+}
+
+impl XInputGamepad {
+    pub fn new() -> Self {
+        XInputGamepad {
+            user_index: None,
+            last_search: None,
+        }
+    }
+
+    fn find_controller(&mut self) -> Option<(u32, XINPUT_STATE)> {
+        let now = Instant::now();
+        if self.last_search.is_some_and(|last| now.duration_since(last) < RECONNECT_INTERVAL) {
+            return None;
+        }
+        self.last_search = Some(now);
+        (0..XUSER_MAX_COUNT).find_map(|user_index| get_state(user_index).map(|state| (user_index, state)))
+    }
+}
+
+fn get_state(user_index: u32) -> Option<XINPUT_STATE> {
+    let mut state = XINPUT_STATE::default();
+    let result = unsafe { XInputGetState(user_index, &mut state) };
+    (result == ERROR_SUCCESS.0).then_some(state)
+}
+
+impl GamepadBackend for XInputGamepad {
+    fn poll(&mut self) -> Option<GamepadState> {
+        let state = match self.user_index.and_then(get_state) {
+            Some(state) => state,
+            None => {
+                let (user_index, state) = self.find_controller()?;
+                self.user_index = Some(user_index);
+                state
+            }
+        };
+        Some(GamepadState {
+            buttons: state.Gamepad.wButtons.0,
+            left_trigger: state.Gamepad.bLeftTrigger,
+            right_trigger: state.Gamepad.bRightTrigger,
+        })
+    }
+}
@@ -1,3 +1,5 @@
+mod simulate;
+
 use serde::{Deserialize, Serialize};
 use std::env;
 use std::fs;
@@ -31,19 +33,34 @@ fn try_main() -> Result<(), Box<dyn std::error::Error>> {
     let xtask_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR")?);
     let project_root = xtask_dir.parent().ok_or("Could not find project root")?;
 
-    let config = get_or_create_config(project_root)?;
-
     match command {
         "deploy" => {
+            let config = get_or_create_config(project_root)?;
             deploy(profile, project_root, &config, force)?;
         }
         "run" => {
+            let config = get_or_create_config(project_root)?;
             deploy(profile, project_root, &config, force)?;
             run(&config, project_root)?;
         }
+        "simulate" => {
+            let scenario_path = args.get(2).ok_or("Usage: cargo simulate <scenario.json>")?;
+            simulate::simulate(Path::new(scenario_path), project_root)?;
+        }
+        "migrate-config" => {
+            let ini_path = args.get(2).ok_or("Usage: cargo migrate-config <eldenring_remapper.ini>")?;
+            let mut diagnostics = Vec::new();
+            let backup_path = remapper_core::rewrite_config_file(Path::new(ini_path), &mut diagnostics)?;
+            for diagnostic in &diagnostics {
+                eprintln!("warning: {}", diagnostic);
+            }
+            if let Some(backup_path) = backup_path {
+                println!("Migrated {} to config version {}, the original was saved to {}", ini_path, remapper_core::CONFIG_VERSION, backup_path.display());
+            }
+        }
         _ => {
             eprintln!("Unknown command '{}'", command);
-            eprintln!("Available commands: deploy, run");
+            eprintln!("Available commands: deploy, run, simulate, migrate-config");
             std::process::exit(1);
         }
     }
@@ -114,14 +131,14 @@ fn run(config: &Config, project_root: &Path) -> Result<(), Box<dyn std::error::E
         Some(ext) if ext == "ps1" => {
             let script_path_absolute = script_path.canonicalize()?.display().to_string();
             Command::new("powershell.exe")
-                .args(&["-ExecutionPolicy", "Bypass", "-File", &script_path_absolute.replacen(r"\\?\", "", 1)])
+                .args(["-ExecutionPolicy", "Bypass", "-File", &script_path_absolute.replacen(r"\\?\", "", 1)])
                 .current_dir(project_root)
                 .spawn()?;
         },
         _ => {
             let powershell_command = format!("Start-Process -FilePath '{}' -WorkingDirectory '{}'", script_path.display(), script_dir.display());
             Command::new("powershell")
-                .args(&["-Command", &powershell_command])
+                .args(["-Command", &powershell_command])
                 .spawn()?;
         }
     }
@@ -216,7 +233,7 @@ fn deploy_file(src: &Path, dest_dir_str: &str, force: bool, project_root: &Path)
         return Err(format!("Source file {:?} not found.", src).into());
     }
 
-    fs::copy(src, &dest_dir.join(file_name))?;
+    fs::copy(src, dest_dir.join(file_name))?;
     println!("  -> Copied {:?} to {}", file_name, dest_dir_str);
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,250 @@
+use remapper_core::{
+    build_keybindings,
+    build_layers,
+    describe_config_changes,
+    load_config,
+    Diagnostic,
+    GameState,
+    InputFrame,
+    InputSource,
+    Keycode,
+    MagicSlots,
+    Remapper,
+    ReplayInputSource,
+};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+enum SimulatedHudType {
+    Off,
+    On,
+    Auto,
+}
+
+#[derive(Deserialize)]
+struct Scenario {
+    // Path to the ini, relative to the scenario file. Defaults to the ini shipped in the project root
+    config: Option<String>,
+    // Input recording to play back instead of the keys in the timeline, relative to the scenario file
+    replay: Option<String>,
+    #[serde(default = "default_frame_milliseconds")]
+    frame_milliseconds: u64,
+    // Ignored when replaying, the recording decides how many frames there are
+    #[serde(default)]
+    frames: u64,
+    param_ids: Vec<i32>,
+    #[serde(default)]
+    selected_slot: i32,
+    hud_type: SimulatedHudType,
+    player_hp: i32,
+    #[serde(default)]
+    timeline: Vec<TimelineEvent>,
+}
+
+// Everything set by an event sticks until a later event changes it again
+#[derive(Deserialize)]
+struct TimelineEvent {
+    frame: u64,
+    keys: Option<Vec<String>>,
+    // Swaps in another ini, the same way the dll hot-reloads an edited config
+    config: Option<String>,
+    param_ids: Option<Vec<i32>>,
+    selected_slot: Option<i32>,
+    hud_type: Option<SimulatedHudType>,
+    player_hp: Option<i32>,
+}
+
+fn default_frame_milliseconds() -> u64 {
+    16
+}
+
+struct SimulatedGame {
+    param_ids: Vec<i32>,
+    selected_slot: i32,
+    hud_type: SimulatedHudType,
+    player_hp: i32,
+}
+
+impl MagicSlots for SimulatedGame {
+    fn param_ids(&self) -> Vec<i32> {
+        self.param_ids.clone()
+    }
+
+    fn selected_slot(&self) -> i32 {
+        self.selected_slot
+    }
+
+    fn set_selected_slot(&mut self, slot: i32) {
+        self.selected_slot = slot;
+    }
+}
+
+impl GameState for SimulatedGame {
+    type HudType = SimulatedHudType;
+
+    const HUD_ON: SimulatedHudType = SimulatedHudType::On;
+
+    fn player_hp(&self) -> i32 {
+        self.player_hp
+    }
+
+    fn hud_type(&self) -> SimulatedHudType {
+        self.hud_type
+    }
+
+    fn set_hud_type(&mut self, hud_type: SimulatedHudType) {
+        self.hud_type = hud_type;
+    }
+}
+
+// Feeds the keys from the scenario timeline at a fixed frame rate
+struct TimelineInputSource {
+    keys: Vec<(u64, Vec<Keycode>)>,
+    pressed_keys: Vec<Keycode>,
+    frame: u64,
+    frames: u64,
+    frame_duration: Duration,
+    started: Instant,
+}
+
+impl InputSource for TimelineInputSource {
+    fn poll(&mut self) -> Option<InputFrame> {
+        if self.frame >= self.frames {
+            return None;
+        }
+
+        if let Some((_, pressed_keys)) = self.keys.iter().rev().find(|(frame, _)| *frame <= self.frame) {
+            self.pressed_keys = pressed_keys.clone();
+        }
+
+        let timestamp = self.started + self.frame_duration * self.frame as u32;
+        self.frame += 1;
+
+        Some(InputFrame {
+            timestamp,
+            pressed_keys: self.pressed_keys.clone(),
+        })
+    }
+}
+
+fn print_diagnostic(diagnostic: &Diagnostic) {
+    if diagnostic.kind.is_warning() {
+        eprintln!("warning: {}", diagnostic);
+    } else {
+        eprintln!("info: {}", diagnostic);
+    }
+}
+
+fn parse_keys(keys: &[String]) -> Result<Vec<Keycode>, Box<dyn std::error::Error>> {
+    keys.iter()
+        .map(|k| k.parse::<Keycode>().map_err(|e| e.into()))
+        .collect()
+}
+
+pub fn simulate(scenario_path: &Path, project_root: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let content = fs::read_to_string(scenario_path)
+        .map_err(|e| format!("Failed to read scenario {:?}: {}", scenario_path, e))?;
+    let mut scenario: Scenario = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse scenario {:?}: {}", scenario_path, e))?;
+    scenario.timeline.sort_by_key(|e| e.frame);
+
+    let config_path = match &scenario.config {
+        Some(path) => scenario_path.parent().unwrap_or(Path::new(".")).join(path),
+        None => project_root.join("eldenring_remapper.ini"),
+    };
+    let config = load_config(&config_path);
+    for diagnostic in &config.diagnostics {
+        print_diagnostic(diagnostic);
+    }
+    let mut other_settings_config = config.other_settings;
+    let mut keybinds_config = config.keybinds;
+    let mut layers_config = config.layers;
+
+    let start = Instant::now();
+    let mut remapper = Remapper::new(build_keybindings(&keybinds_config), build_layers(&layers_config), &other_settings_config, start);
+
+    let mut input_source: Box<dyn InputSource> = match &scenario.replay {
+        Some(path) => {
+            let replay_path = scenario_path.parent().unwrap_or(Path::new(".")).join(path);
+            Box::new(ReplayInputSource::open(&replay_path, start)?)
+        }
+        None => {
+            let keys = scenario.timeline.iter()
+                .filter_map(|e| e.keys.as_ref().map(|keys| (e.frame, keys)))
+                .map(|(frame, keys)| parse_keys(keys).map(|keys| (frame, keys)))
+                .collect::<Result<Vec<(u64, Vec<Keycode>)>, _>>()?;
+            Box::new(TimelineInputSource {
+                keys,
+                pressed_keys: Vec::new(),
+                frame: 0,
+                frames: scenario.frames,
+                frame_duration: Duration::from_millis(scenario.frame_milliseconds),
+                started: start,
+            })
+        }
+    };
+
+    let mut game = SimulatedGame {
+        param_ids: scenario.param_ids,
+        selected_slot: scenario.selected_slot,
+        hud_type: scenario.hud_type,
+        player_hp: scenario.player_hp,
+    };
+    let mut events = scenario.timeline.iter().peekable();
+
+    println!("Simulating with config {}", config_path.display());
+
+    let mut frame = 0;
+    while let Some(input) = input_source.poll() {
+        while let Some(event) = events.next_if(|e| e.frame <= frame) {
+            if let Some(path) = &event.config {
+                let reload_path = scenario_path.parent().unwrap_or(Path::new(".")).join(path);
+                let config = load_config(&reload_path);
+                for diagnostic in &config.diagnostics {
+                    print_diagnostic(diagnostic);
+                }
+                println!("Reloading config from {}", reload_path.display());
+                for change in describe_config_changes(&keybinds_config, &layers_config, &other_settings_config, &config.keybinds, &config.layers, &config.other_settings) {
+                    println!("  {}", change);
+                }
+                remapper.reload(build_keybindings(&config.keybinds), build_layers(&config.layers), &config.other_settings);
+                keybinds_config = config.keybinds;
+                layers_config = config.layers;
+                other_settings_config = config.other_settings;
+            }
+            if let Some(param_ids) = &event.param_ids {
+                game.param_ids = param_ids.clone();
+            }
+            if let Some(selected_slot) = event.selected_slot {
+                game.selected_slot = selected_slot;
+            }
+            if let Some(hud_type) = event.hud_type {
+                game.hud_type = hud_type;
+            }
+            if let Some(player_hp) = event.player_hp {
+                game.player_hp = player_hp;
+            }
+        }
+
+        remapper.run_frame(&mut game, &input.pressed_keys, input.timestamp);
+
+        let keys = input.pressed_keys.iter().map(Keycode::as_str).collect::<Vec<_>>().join("+");
+        println!(
+            "frame {:>5} {:>7}ms  slot {:>2}  hud {:<4}  hp {:>5}  keys {}",
+            frame,
+            input.timestamp.duration_since(start).as_millis(),
+            game.selected_slot,
+            format!("{:?}", game.hud_type),
+            game.player_hp,
+            if keys.is_empty() { "-" } else { &keys },
+        );
+        for message in remapper.take_messages() {
+            println!("  {}", message);
+        }
+        frame += 1;
+    }
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use std::process::Command;
+
+const CONFIG: &str = "[keybinds]\nmemory_slot_2 = Shift+2\ncycle_forward = 3\n";
+
+// Frames are half a second apart so the HUD gets restored within the scenario
+const SCENARIO: &str = r#"{
+    "config": "simulate.ini",
+    "frame_milliseconds": 500,
+    "frames": 16,
+    "param_ids": [4000, 4010, 4020, -1],
+    "hud_type": "Off",
+    "player_hp": 100,
+    "timeline": [
+        { "frame": 1, "keys": ["LShift", "Key2"] },
+        { "frame": 2, "keys": [] },
+        { "frame": 3, "keys": ["Key3"] },
+        { "frame": 4, "keys": [], "player_hp": 0 },
+        { "frame": 5, "keys": ["Key3"] },
+        { "frame": 6, "keys": [], "player_hp": 100 },
+        { "frame": 7, "keys": ["Key3"] },
+        { "frame": 8, "keys": [] }
+    ]
+}"#;
+
+#[test]
+fn scripted_scenario_prints_the_slot_and_hud_of_every_frame() {
+    let dir = std::env::temp_dir().join(format!("xtask_simulate_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("simulate.ini"), CONFIG).unwrap();
+    std::fs::write(dir.join("scenario.json"), SCENARIO).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_xtask"))
+        .arg("simulate")
+        .arg(dir.join("scenario.json"))
+        .output()
+        .unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let frames = stdout.lines()
+        .filter_map(|line| {
+            let fields = line.split_whitespace().collect::<Vec<&str>>();
+            (fields.first() == Some(&"frame")).then(|| (fields[4].parse::<i32>().unwrap(), fields[6].to_string()))
+        })
+        .collect::<Vec<(i32, String)>>();
+    let expected = [
+        (0, "Off"),
+        (1, "On"),
+        (1, "On"),
+        (2, "On"),
+        // Dead, so nothing is pressed
+        (2, "On"),
+        (2, "On"),
+        (2, "On"),
+        // Cycling skips the empty slot and wraps around
+        (0, "On"),
+        (0, "On"),
+        (0, "On"),
+        (0, "On"),
+        (0, "On"),
+        (0, "On"),
+        (0, "On"),
+        // Restored three seconds after the last selection
+        (0, "Off"),
+        (0, "Off"),
+    ];
+    assert_eq!(frames, expected.map(|(slot, hud)| (slot, hud.to_string())));
+
+    let messages = stdout.lines().filter(|line| line.starts_with("  ")).collect::<Vec<&str>>();
+    assert_eq!(
+        messages,
+        ["  selected slot 2 (Swift Glintstone Shard)", "  selected slot 3 (Glintstone Cometshard)", "  selected slot 1 (Glintstone Pebble)"],
+    );
+}